@@ -0,0 +1,48 @@
+//! Wake a thread which is blocked in `read_event_blocking`.
+//!
+//! Run with `cargo run --example waker --features multithreading`.
+
+extern crate x11_wrapper;
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use x11_wrapper::XlibHandle;
+use x11_wrapper::core::event::{Event, EventBuffer};
+
+fn main() {
+    let xlib_handle = XlibHandle::initialize_xlib().unwrap();
+
+    let mut display = xlib_handle.create_display().unwrap();
+    let waker = display.create_waker().unwrap();
+
+    let (started_sender, started_receiver) = mpsc::channel();
+
+    let reader = thread::spawn(move || {
+        let mut event_buffer = EventBuffer::new();
+
+        started_sender.send(()).unwrap();
+
+        // No window exists, so the wakeup event is the only event.
+        loop {
+            let event = display.read_event_blocking(&mut event_buffer).unwrap();
+
+            if let Event::Wakeup = event.into_event() {
+                return;
+            }
+        }
+    });
+
+    started_receiver.recv().unwrap();
+
+    // Give the reader time to block in XNextEvent.
+    thread::sleep(Duration::from_millis(500));
+
+    let start = Instant::now();
+    waker.wake().unwrap();
+    println!("wake() returned after {:?}", start.elapsed());
+
+    reader.join().unwrap();
+    println!("reader thread received Event::Wakeup after {:?}", start.elapsed());
+}
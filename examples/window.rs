@@ -4,6 +4,7 @@ use x11_wrapper::XlibHandle;
 use x11_wrapper::core::window::input_output::{InputOutputWindowBuilder};
 use x11_wrapper::core::window::WindowProperties;
 use x11_wrapper::core::event::{EventMask, SimpleEvent, EventBuffer};
+use x11_wrapper::core::keysym::Keysym;
 use x11_wrapper::core::utils::Text;
 use x11_wrapper::protocol::Protocols;
 use x11_wrapper::property::ewmh::NetWmStateHandler;
@@ -15,6 +16,8 @@ fn main() {
 
     let xlib_handle = XlibHandle::initialize_xlib().unwrap();
 
+    xlib_handle.set_diagnostic_handler(|diagnostic| eprintln!("x11_wrapper: {:?}", diagnostic));
+
     let mut display = xlib_handle.create_display().unwrap();
 
     println!("display string: {:?}", display.display_string());
@@ -54,34 +57,42 @@ fn main() {
         .unwrap()
         .map_window();
 
-    display.flush_output_buffer();
+    display.flush_output_buffer().unwrap();
 
     let mut net_wm_state_handler = NetWmStateHandler::new(&display).unwrap();
     let mut event_buffer = EventBuffer::new();
 
     loop {
-        let event = display.read_event_blocking(&mut event_buffer).into_event().into_simple_event();
+        let event = match display.read_event_blocking(&mut event_buffer) {
+            Ok(event) => event.into_event().into_simple_event(),
+            Err(_) => {
+                eprintln!("connection to X11 server lost");
+                break;
+            }
+        };
 
         println!("{:?}", &event);
 
         match &event {
-            // Key E
-            &SimpleEvent::KeyRelease { keycode: 26 } => {
-                println!("Window properties:");
-                for property in window.list_properties().atoms() {
-                    let property_name = property.get_name(&display).unwrap();
-                    println!("{}", property_name);
+            &SimpleEvent::KeyRelease { keycode, .. } => {
+                match display.keycode_to_keysym(keycode as u8, 0, 0) {
+                    Some(Keysym::e) => {
+                        println!("Window properties:");
+                        for property in window.list_properties().atoms() {
+                            let property_name = property.get_name(&display).unwrap();
+                            println!("{}", property_name);
+                        }
+                    }
+                    Some(Keysym::w) => {
+                        let event = net_wm_state_handler.toggle_fullscreen(&window);
+                        default_screen.send_ewmh_client_message_event(event).unwrap();
+                    }
+                    Some(Keysym::q) => {
+                        window.iconify(&default_screen).unwrap();
+                    }
+                    _ => (),
                 }
             }
-            // Key W
-            &SimpleEvent::KeyRelease { keycode: 25 } => {
-                let event = net_wm_state_handler.toggle_fullscreen(&window);
-                default_screen.send_ewmh_client_message_event(event).unwrap();
-            }
-            // Key Q
-            &SimpleEvent::KeyRelease { keycode: 24 } => {
-                window.iconify(&default_screen).unwrap();
-            }
             &SimpleEvent::ClientMessage(e) => {
                 if delete_window_handler.check_event(e) {
                     break;
@@ -1,14 +1,39 @@
 use std::ptr;
-use std::os::raw::{c_int, c_long, c_ulong};
+#[cfg(feature = "multithreading")]
+use std::cell::RefCell;
+use std::env;
+use std::os::raw::{c_int, c_long, c_ulong, c_void};
+use std::slice;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::io;
+use std::time::{Duration, Instant};
+use std::net::{TcpStream, ToSocketAddrs};
 use std::marker::PhantomData;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
+use std::sync::Arc;
+#[cfg(feature = "multithreading")]
+use std::sync::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use x11::xlib;
+#[cfg(feature = "xlib-xcb")]
+use x11::xlib_xcb;
 
 use super::XlibHandle;
+use super::error::{self, ConnectionLostError, DisplayErrorState, QueryResult};
+use super::image::{BitOrder, ByteOrder, PixmapFormat};
 use super::screen::Screen;
+use super::utils::{to_xlib_bool, Atom, AtomCache, AtomName};
 use super::visual::Visual;
-use super::event::{send_event, EventBuffer, EventCreator, EventMask, RawEvent};
+#[cfg(feature = "multithreading")]
+use super::waker::{EventLoopWaker, WakerTarget};
+use super::event::{send_event, EventBuffer, EventCreator, EventMask, EventPredicate, RawEvent};
+use super::event::owned::OwnedEvent;
+
+use extension::ExtensionInfo;
+
+use libc;
 
 #[cfg(feature = "multithreading")]
 unsafe impl Send for DisplayHandle {}
@@ -20,6 +45,11 @@ unsafe impl Sync for DisplayHandle {}
 struct DisplayHandle {
     xlib_handle: XlibHandle,
     raw_display: *mut xlib::Display,
+    error_state: Arc<DisplayErrorState>,
+    synchronous: AtomicBool,
+    atom_cache: AtomCache,
+    #[cfg(feature = "multithreading")]
+    waker_target: Mutex<Option<WakerTarget>>,
     _marker: PhantomData<xlib::Display>,
 }
 
@@ -31,6 +61,11 @@ impl DisplayHandle {
         Self {
             xlib_handle,
             raw_display,
+            error_state: error::register_display(raw_display),
+            synchronous: AtomicBool::new(false),
+            atom_cache: AtomCache::new(),
+            #[cfg(feature = "multithreading")]
+            waker_target: Mutex::new(None),
             _marker: PhantomData,
         }
     }
@@ -46,6 +81,8 @@ impl Drop for DisplayHandle {
 
             // TODO: check BadGC error
         }
+
+        error::unregister_display(self.raw_display, &self.error_state);
     }
 }
 
@@ -60,13 +97,34 @@ pub struct X11Display {
 
 impl X11Display {
     /// Create new connection to X11 server.
-    pub(crate) fn new(xlib_handle: XlibHandle) -> Result<Self, ()> {
-        // TODO: display_name string support
+    ///
+    /// If `display_name` is `None`, value of environment
+    /// variable `DISPLAY` is used.
+    pub(crate) fn new(
+        xlib_handle: XlibHandle,
+        display_name: Option<&str>,
+    ) -> Result<Self, OpenDisplayError> {
+        let c_display_name = match display_name {
+            Some(name) => {
+                if DisplayName::parse(name).is_none() {
+                    return Err(OpenDisplayError::InvalidName);
+                }
+
+                Some(CString::new(name).map_err(|_| OpenDisplayError::InvalidName)?)
+            }
+            None => None,
+        };
 
-        let raw_display = unsafe { xlib_function!(&xlib_handle, XOpenDisplay(None, ptr::null())) };
+        let display_name_ptr = match c_display_name {
+            Some(ref name) => name.as_ptr(),
+            None => ptr::null(),
+        };
+
+        let raw_display =
+            unsafe { xlib_function!(&xlib_handle, XOpenDisplay(None, display_name_ptr)) };
 
         if raw_display.is_null() {
-            return Err(());
+            return Err(OpenDisplayError::from_failed_connection(display_name));
         }
 
         #[cfg(feature = "multithreading")]
@@ -88,6 +146,27 @@ impl X11Display {
         self.display_handle.raw_display
     }
 
+    pub(crate) fn error_state(&self) -> &Arc<DisplayErrorState> {
+        &self.display_handle.error_state
+    }
+
+    /// Returns true if Xlib has reported that connection
+    /// to X11 server is lost.
+    ///
+    /// See crate level documentation for more information.
+    pub fn connection_lost(&self) -> bool {
+        self.display_handle.error_state.connection_lost()
+    }
+
+    /// Returns error if connection to X11 server is lost.
+    pub fn check_connection(&self) -> Result<(), ConnectionLostError> {
+        if self.connection_lost() {
+            Err(ConnectionLostError)
+        } else {
+            Ok(())
+        }
+    }
+
     /// XConnectionNumber
     pub fn connection_number(&self) -> c_int {
         unsafe { xlib_function!(self.xlib_handle(), XConnectionNumber(Some(self.raw_display()))) }
@@ -105,12 +184,37 @@ impl X11Display {
         Screen::new(self.clone(), screen)
     }
 
-    // TODO: Implement XScreenOfDisplay
-    /*
-    pub fn screen_of_display(&self) {
-        unimplemented!()
+    /// Returns `None` if `screen_number` is not in
+    /// range [0; `screen_count()`).
+    ///
+    /// XScreenOfDisplay
+    pub fn screen(&self, screen_number: c_int) -> Option<Screen> {
+        if screen_number < 0 || screen_number >= self.screen_count() {
+            return None;
+        }
+
+        let screen = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XScreenOfDisplay(Some(self.raw_display()), screen_number)
+            )
+        };
+
+        if screen.is_null() {
+            None
+        } else {
+            Some(Screen::new(self.clone(), screen))
+        }
+    }
+
+    /// All screens of the display ordered by screen number.
+    ///
+    /// XScreenCount, XScreenOfDisplay
+    pub fn screens(&self) -> Vec<Screen> {
+        (0..self.screen_count())
+            .filter_map(|screen_number| self.screen(screen_number))
+            .collect()
     }
-    */
 
     /// XDisplayString
     pub fn display_string(&self) -> &CStr {
@@ -198,7 +302,103 @@ impl X11Display {
         unsafe { xlib_function!(self.xlib_handle(), XVendorRelease(Some(self.raw_display()))) }
     }
 
-    // TODO: section "Image Format Functions and Macros" from xlib manual
+    /// Pixmap formats supported by X11 server.
+    ///
+    /// XListPixmapFormats, XFree
+    pub fn pixmap_formats(&self) -> Vec<PixmapFormat> {
+        let mut count = 0;
+
+        let formats = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XListPixmapFormats(Some(self.raw_display()), &mut count)
+            )
+        };
+
+        if formats.is_null() {
+            return vec![];
+        }
+
+        let format_list = if count > 0 {
+            unsafe { slice::from_raw_parts(formats, count as usize) }
+                .iter()
+                .map(|values| PixmapFormat::from(*values))
+                .collect()
+        } else {
+            vec![]
+        };
+
+        unsafe {
+            xlib_function!(self.xlib_handle(), XFree(None, formats as *mut c_void));
+        }
+
+        format_list
+    }
+
+    /// Byte order of images and bitmap units.
+    ///
+    /// XImageByteOrder
+    pub fn image_byte_order(&self) -> QueryResult<ByteOrder> {
+        let order =
+            unsafe { xlib_function!(self.xlib_handle(), XImageByteOrder(Some(self.raw_display()))) };
+
+        ByteOrder::from_xlib(order)
+    }
+
+    /// Size of bitmap scanline unit in bits.
+    ///
+    /// XBitmapUnit
+    pub fn bitmap_unit(&self) -> c_int {
+        unsafe { xlib_function!(self.xlib_handle(), XBitmapUnit(Some(self.raw_display()))) }
+    }
+
+    /// Bit order inside bitmap scanline unit.
+    ///
+    /// XBitmapBitOrder
+    pub fn bitmap_bit_order(&self) -> QueryResult<BitOrder> {
+        let order =
+            unsafe { xlib_function!(self.xlib_handle(), XBitmapBitOrder(Some(self.raw_display()))) };
+
+        BitOrder::from_xlib(order)
+    }
+
+    /// Bitmap scanline length in bits is multiple of this value.
+    ///
+    /// XBitmapPad
+    pub fn bitmap_pad(&self) -> c_int {
+        unsafe { xlib_function!(self.xlib_handle(), XBitmapPad(Some(self.raw_display()))) }
+    }
+
+    /// Atoms and atom names queried with this display.
+    pub fn atom_cache(&self) -> &AtomCache {
+        &self.display_handle.atom_cache
+    }
+
+    /// Query multiple atoms with one round trip to the X server. Atoms
+    /// which are already in the `atom_cache` are not queried.
+    ///
+    /// Returned list has an item for every name in `atom_names`. The item is
+    /// `None` if the atom does not exist and `only_if_exists` is `true`.
+    ///
+    /// XInternAtoms
+    pub fn intern_atoms(&self, atom_names: &[AtomName], only_if_exists: bool) -> Vec<Option<Atom>> {
+        self.atom_cache()
+            .intern_atoms(self, atom_names, only_if_exists)
+    }
+
+    /// Returns `None` if X11 server does not support extension `name`.
+    ///
+    /// XQueryExtension
+    pub fn query_extension(&self, name: &str) -> Option<ExtensionInfo> {
+        ExtensionInfo::query(self, name)
+    }
+
+    /// Information of every extension supported by X11 server.
+    ///
+    /// XListExtensions, XQueryExtension, XFreeExtensionList
+    pub fn extensions(&self) -> Vec<ExtensionInfo> {
+        ExtensionInfo::query_all(self)
+    }
 
     /// XNoOp
     pub fn send_no_op_request(&self) {
@@ -212,24 +412,180 @@ impl X11Display {
         Visual::new(self.clone(), visual_id)
     }
 
+    /// Returns error if connection to X11 server is lost
+    /// before or while flushing.
+    ///
     /// XFlush
-    pub fn flush_output_buffer(&self) {
+    pub fn flush_output_buffer(&self) -> Result<(), ConnectionLostError> {
+        self.check_connection()?;
+
         unsafe {
             xlib_function!(self.xlib_handle(), XFlush(Some(self.raw_display())));
         }
+
+        self.check_connection()
     }
 
+    /// Waits until X11 server has processed all sent requests.
+    ///
+    /// Returns error if connection to X11 server is lost
+    /// before or while waiting.
+    ///
     /// XSync
-    pub fn sync(&self) {
+    pub fn sync(&self) -> Result<(), ConnectionLostError> {
+        self.check_connection()?;
+
         unsafe {
             xlib_function!(self.xlib_handle(), XSync(Some(self.raw_display()), xlib::False));
         }
+
+        self.check_connection()
+    }
+
+    /// Enable or disable synchronous mode. In synchronous mode Xlib waits
+    /// until X11 server has processed every request, so errors from
+    /// `check_errors` can be matched with the function call which caused them.
+    /// Synchronous mode makes Xlib much slower, so use it only for debugging.
+    ///
+    /// XSynchronize
+    pub fn set_synchronous(&self, enable: bool) {
+        unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XSynchronize(Some(self.raw_display()), to_xlib_bool(enable))
+            );
+        }
+
+        self.display_handle.synchronous.store(enable, Ordering::SeqCst);
+    }
+
+    /// Returns true if synchronous mode is enabled with `set_synchronous` or
+    /// `synchronous_guard`.
+    pub fn is_synchronous(&self) -> bool {
+        self.display_handle.synchronous.load(Ordering::SeqCst)
+    }
+
+    /// Enable synchronous mode until the returned guard is dropped. After that
+    /// the previous mode is restored.
+    ///
+    /// See also documentation of `set_synchronous`.
+    ///
+    /// XSynchronize
+    pub fn synchronous_guard(&self) -> SynchronousModeGuard<'_> {
+        let previous_mode = self.is_synchronous();
+
+        self.set_synchronous(true);
+
+        SynchronousModeGuard {
+            display: self,
+            previous_mode,
+        }
+    }
+
+    /// Lock the display for the current thread until the guard is dropped.
+    /// Other threads can't use the display while it is locked.
+    ///
+    /// Function calls made with the lock held do not lock the display
+    /// by themselves, so this also removes the locking overhead from
+    /// every call.
+    ///
+    /// Don't call blocking event reading methods like `read_event_blocking`
+    /// while holding the lock, because other threads, including
+    /// `EventLoopWaker::wake`, can't use the display until an event arrives.
+    ///
+    /// XLockDisplay
+    #[cfg(feature = "multithreading")]
+    pub fn lock(&self) -> DisplayLockGuard<'_> {
+        DisplayLockGuard::new(self.xlib_handle(), self.raw_display())
+    }
+
+    /// Create a handle which can wake a thread waiting for events
+    /// of this display. Waiting thread receives `Event::Wakeup`.
+    ///
+    /// Wakeup events are sent to a hidden window which is created
+    /// when this method is called the first time.
+    ///
+    /// Returns error if creating the window failed.
+    ///
+    /// XInternAtom, XCreateWindow
+    #[cfg(feature = "multithreading")]
+    pub fn create_waker(&self) -> Result<EventLoopWaker, ()> {
+        if let Some(target) = *self.lock_waker_target() {
+            return Ok(EventLoopWaker::new(self.clone(), target));
+        }
+
+        // Don't hold the mutex while calling Xlib functions.
+        let new_target = WakerTarget::new(self)?;
+
+        let target = *self.lock_waker_target().get_or_insert(new_target);
+
+        Ok(EventLoopWaker::new(self.clone(), target))
+    }
+
+    #[cfg(feature = "multithreading")]
+    fn lock_waker_target(&self) -> MutexGuard<'_, Option<WakerTarget>> {
+        match self.display_handle.waker_target.lock() {
+            Ok(target) => target,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    /// Set `EventBuffer`'s wakeup flag after reading an event to it.
+    pub(crate) fn mark_wakeup_event(&self, event_buffer: &mut EventBuffer) {
+        #[cfg(feature = "multithreading")]
+        {
+            let wakeup = match *self.lock_waker_target() {
+                Some(target) => target.matches(event_buffer.raw_event()),
+                None => false,
+            };
+
+            event_buffer.set_wakeup(wakeup);
+        }
+
+        #[cfg(not(feature = "multithreading"))]
+        event_buffer.set_wakeup(false);
+    }
+
+    /// XCB connection of the display. Use this with XCB bindings.
+    ///
+    /// XGetXCBConnection
+    #[cfg(feature = "xlib-xcb")]
+    pub fn xcb_connection(&self) -> *mut xlib_xcb::xcb_connection_t {
+        unsafe {
+            xlib_xcb_function!(self.xlib_handle(), XGetXCBConnection(self.raw_display()))
+        }
+    }
+
+    /// Select which library reads events from the connection. Default
+    /// owner is Xlib.
+    ///
+    /// If the owner is XCB, event reading methods of this library
+    /// must not be used. Change the owner before sending any requests with
+    /// the display.
+    ///
+    /// XSetEventQueueOwner
+    #[cfg(feature = "xlib-xcb")]
+    pub fn set_event_queue_owner(&self, owner: EventQueueOwner) {
+        let owner = match owner {
+            EventQueueOwner::Xlib => xlib_xcb::XEventQueueOwner::XlibOwnsEventQueue,
+            EventQueueOwner::Xcb => xlib_xcb::XEventQueueOwner::XCBOwnsEventQueue,
+        };
+
+        unsafe {
+            xlib_xcb_function!(self.xlib_handle(), XSetEventQueueOwner(self.raw_display(), owner));
+        }
     }
 
     /// Try to read event from Xlib event queue to `EventBuffer`.
     ///
+    /// Returns `None` also if connection to X11 server is lost.
+    ///
     /// XEventsQueued, XNextEvent
     pub fn read_event<'a>(&mut self, event_buffer: &'a mut EventBuffer) -> Option<RawEvent<'a>> {
+        if self.connection_lost() {
+            return None;
+        }
+
         let mut event_count = self.events_queued(EventsQueuedMode::QueuedAlready);
         if event_count <= 0 {
             event_count = self.events_queued(EventsQueuedMode::QueuedAfterReading);
@@ -239,21 +595,384 @@ impl X11Display {
             }
         }
 
-        Some(self.read_event_blocking(event_buffer))
+        self.read_event_blocking(event_buffer).ok()
     }
 
     /// Blocks until event is received.
     ///
+    /// Returns error if connection to X11 server is lost
+    /// before or while waiting the event.
+    ///
     /// XNextEvent
-    pub fn read_event_blocking<'a>(&mut self, event_buffer: &'a mut EventBuffer) -> RawEvent<'a> {
+    pub fn read_event_blocking<'a>(
+        &mut self,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Result<RawEvent<'a>, ConnectionLostError> {
+        self.check_connection()?;
+
+        // Holding XLockDisplay while waiting would block other threads
+        // until the next event arrives, so don't lock the display. Xlib's
+        // internal locking is released while XNextEvent waits.
         unsafe {
             xlib_function!(
                 self.xlib_handle(),
-                XNextEvent(Some(self.raw_display()), event_buffer.event_mut_ptr())
+                XNextEvent(None, self.raw_display(), event_buffer.event_mut_ptr())
             );
         }
 
-        RawEvent::new(event_buffer)
+        // If connection was lost, XNextEvent returns
+        // without modifying the event buffer.
+        self.check_connection()?;
+
+        self.mark_wakeup_event(event_buffer);
+
+        Ok(RawEvent::new(event_buffer))
+    }
+
+    /// Moves all events which are available without blocking to the end of
+    /// `events`. Event count is checked only once, so this is
+    /// faster than calling `read_event` for every event.
+    ///
+    /// With crate feature `multithreading` the display is locked
+    /// once for all events.
+    ///
+    /// Returns count of added events. Reading stops if
+    /// connection to X11 server is lost.
+    ///
+    /// XEventsQueued, XNextEvent
+    pub fn drain_events(&mut self, events: &mut Vec<OwnedEvent>) -> usize {
+        if self.connection_lost() {
+            return 0;
+        }
+
+        #[cfg(feature = "multithreading")]
+        let _lock = self.lock();
+
+        let event_count = self.events_queued(EventsQueuedMode::QueuedAfterReading);
+        if event_count <= 0 {
+            return 0;
+        }
+
+        events.reserve(event_count as usize);
+
+        let mut event_buffer = EventBuffer::new();
+        let mut added = 0;
+
+        for _ in 0..event_count {
+            unsafe {
+                xlib_function!(
+                    self.xlib_handle(),
+                    XNextEvent(Some(self.raw_display()), event_buffer.event_mut_ptr())
+                );
+            }
+
+            if self.connection_lost() {
+                break;
+            }
+
+            self.mark_wakeup_event(&mut event_buffer);
+
+            events.push(OwnedEvent::from(RawEvent::new(&event_buffer)));
+            added += 1;
+        }
+
+        added
+    }
+
+    /// Blocks until event is received, but does not remove the
+    /// event from the event queue.
+    ///
+    /// Returns error if connection to X11 server is lost
+    /// before or while waiting the event.
+    ///
+    /// XPeekEvent
+    pub fn peek_event<'a>(
+        &mut self,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Result<RawEvent<'a>, ConnectionLostError> {
+        self.check_connection()?;
+
+        // Don't lock the display while waiting. See `read_event_blocking`.
+        unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XPeekEvent(None, self.raw_display(), event_buffer.event_mut_ptr())
+            );
+        }
+
+        self.check_connection()?;
+
+        self.mark_wakeup_event(event_buffer);
+
+        Ok(RawEvent::new(event_buffer))
+    }
+
+    /// Push a copy of the event to the front of the event queue.
+    ///
+    /// XPutBackEvent
+    pub fn put_back_event(&mut self, event_buffer: &EventBuffer) {
+        let mut event = *event_buffer.raw_event();
+
+        unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XPutBackEvent(Some(self.raw_display()), &mut event)
+            );
+        }
+    }
+
+    /// Remove the first event which matches `window_id` and `event_mask`
+    /// from the event queue. Other events are not removed.
+    ///
+    /// Returns `None` if there is no matching event or
+    /// connection to X11 server is lost.
+    ///
+    /// XCheckWindowEvent
+    pub fn check_window_event<'a>(
+        &mut self,
+        window_id: xlib::Window,
+        event_mask: EventMask,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Option<RawEvent<'a>> {
+        if self.connection_lost() {
+            return None;
+        }
+
+        let found = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XCheckWindowEvent(
+                    Some(self.raw_display()),
+                    window_id,
+                    event_mask.bits(),
+                    event_buffer.event_mut_ptr()
+                )
+            )
+        };
+
+        self.checked_event(found, event_buffer)
+    }
+
+    /// Remove the first event with type `event_type` from the event queue.
+    /// Other events are not removed.
+    ///
+    /// Returns `None` if there is no matching event or
+    /// connection to X11 server is lost.
+    ///
+    /// XCheckTypedEvent
+    pub fn check_typed_event<'a>(
+        &mut self,
+        event_type: c_int,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Option<RawEvent<'a>> {
+        if self.connection_lost() {
+            return None;
+        }
+
+        let found = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XCheckTypedEvent(
+                    Some(self.raw_display()),
+                    event_type,
+                    event_buffer.event_mut_ptr()
+                )
+            )
+        };
+
+        self.checked_event(found, event_buffer)
+    }
+
+    /// Remove the first event with type `event_type` and window `window_id`
+    /// from the event queue. Other events are not removed.
+    ///
+    /// Returns `None` if there is no matching event or
+    /// connection to X11 server is lost.
+    ///
+    /// XCheckTypedWindowEvent
+    pub fn check_typed_window_event<'a>(
+        &mut self,
+        window_id: xlib::Window,
+        event_type: c_int,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Option<RawEvent<'a>> {
+        if self.connection_lost() {
+            return None;
+        }
+
+        let found = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XCheckTypedWindowEvent(
+                    Some(self.raw_display()),
+                    window_id,
+                    event_type,
+                    event_buffer.event_mut_ptr()
+                )
+            )
+        };
+
+        self.checked_event(found, event_buffer)
+    }
+
+    /// Blocks until `predicate` returns true for an event and
+    /// removes that event from the event queue. Other events are not removed.
+    ///
+    /// The predicate must not call Xlib functions. If the predicate
+    /// panics, the event is put back to the event queue and the panic
+    /// continues after Xlib function returns.
+    ///
+    /// Returns error if connection to X11 server is lost
+    /// before or while waiting the event.
+    ///
+    /// XIfEvent
+    pub fn if_event<'a, F: FnMut(&xlib::XEvent) -> bool>(
+        &mut self,
+        predicate: F,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Result<RawEvent<'a>, ConnectionLostError> {
+        self.check_connection()?;
+
+        let mut predicate = EventPredicate::new(predicate);
+
+        // Don't lock the display while waiting. See `read_event_blocking`.
+        unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XIfEvent(
+                    None,
+                    self.raw_display(),
+                    event_buffer.event_mut_ptr(),
+                    Some(EventPredicate::<F>::call),
+                    predicate.as_argument()
+                )
+            );
+        }
+
+        if predicate.panicked() {
+            // XIfEvent removed the event which the predicate panicked with.
+            self.put_back_event(event_buffer);
+            predicate.resume_panic();
+        }
+
+        self.check_connection()?;
+
+        self.mark_wakeup_event(event_buffer);
+
+        Ok(RawEvent::new(event_buffer))
+    }
+
+    /// Like `if_event`, but returns `None` immediately if there is no
+    /// matching event in the event queue. Returns `None` also if
+    /// connection to X11 server is lost.
+    ///
+    /// XCheckIfEvent
+    pub fn check_if_event<'a, F: FnMut(&xlib::XEvent) -> bool>(
+        &mut self,
+        predicate: F,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Option<RawEvent<'a>> {
+        if self.connection_lost() {
+            return None;
+        }
+
+        let mut predicate = EventPredicate::new(predicate);
+
+        let found = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XCheckIfEvent(
+                    Some(self.raw_display()),
+                    event_buffer.event_mut_ptr(),
+                    Some(EventPredicate::<F>::call),
+                    predicate.as_argument()
+                )
+            )
+        };
+
+        if predicate.panicked() {
+            // XCheckIfEvent removed the event which the predicate panicked with.
+            if found != xlib::False {
+                self.put_back_event(event_buffer);
+            }
+
+            predicate.resume_panic();
+        }
+
+        self.checked_event(found, event_buffer)
+    }
+
+    /// Convert return value of XCheck*Event functions.
+    fn checked_event<'a>(
+        &self,
+        found: xlib::Bool,
+        event_buffer: &'a mut EventBuffer,
+    ) -> Option<RawEvent<'a>> {
+        if found == xlib::False || self.connection_lost() {
+            return None;
+        }
+
+        self.mark_wakeup_event(event_buffer);
+
+        Some(RawEvent::new(event_buffer))
+    }
+
+    /// Waits until event is received or `timeout` is elapsed. Output
+    /// buffer is flushed before waiting.
+    ///
+    /// Returns `None` if there was no event before the timeout or connection
+    /// to X11 server is lost. Timeout which is too large for `Instant`,
+    /// for example `Duration::MAX`, waits without a timeout.
+    ///
+    /// XFlush, XEventsQueued, XNextEvent
+    pub fn wait_event_timeout<'a>(
+        &mut self,
+        event_buffer: &'a mut EventBuffer,
+        timeout: Duration,
+    ) -> Option<RawEvent<'a>> {
+        if self.flush_output_buffer().is_err() {
+            return None;
+        }
+
+        // `None` means that there is no deadline.
+        let deadline = Instant::now().checked_add(timeout);
+
+        loop {
+            if self.events_queued(EventsQueuedMode::QueuedAfterReading) > 0 {
+                return self.read_event_blocking(event_buffer).ok();
+            }
+
+            if self.connection_lost() {
+                return None;
+            }
+
+            let poll_timeout = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+
+                    // Round up so that the loop does not spin when
+                    // less than a millisecond is remaining.
+                    let remaining_millis = (deadline - now).as_nanos().div_ceil(1_000_000);
+                    remaining_millis.min(c_int::MAX as u128) as c_int
+                }
+                None => -1,
+            };
+
+            let mut poll_fd = libc::pollfd {
+                fd: self.connection_number(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+
+            let result = unsafe { libc::poll(&mut poll_fd, 1, poll_timeout) };
+
+            if result < 0 && io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                return None;
+            }
+        }
     }
 
     /// Sends new event.
@@ -282,6 +1001,227 @@ impl X11Display {
     }
 }
 
+impl AsRawFd for X11Display {
+    /// File descriptor of the connection to X11 server. Use this for
+    /// waiting events with `poll`, `epoll` or similar.
+    ///
+    /// Xlib may read events from the connection to the event queue at any
+    /// function call, so check the queue with `read_event` before waiting
+    /// for the file descriptor.
+    ///
+    /// XConnectionNumber
+    fn as_raw_fd(&self) -> RawFd {
+        self.connection_number()
+    }
+}
+
+/// See `X11Display::set_event_queue_owner`.
+#[cfg(feature = "xlib-xcb")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventQueueOwner {
+    Xlib,
+    Xcb,
+}
+
+/// Restores previous synchronous mode when dropped. See
+/// `X11Display::synchronous_guard`.
+#[derive(Debug)]
+pub struct SynchronousModeGuard<'a> {
+    display: &'a X11Display,
+    previous_mode: bool,
+}
+
+impl<'a> Drop for SynchronousModeGuard<'a> {
+    /// XSynchronize
+    fn drop(&mut self) {
+        self.display.set_synchronous(self.previous_mode);
+    }
+}
+
+#[cfg(feature = "multithreading")]
+thread_local! {
+    /// Displays locked by `DisplayLockGuard`s of the current thread.
+    static LOCKED_DISPLAYS: RefCell<Vec<*mut xlib::Display>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns true if the current thread holds a `DisplayLockGuard` for
+/// `raw_display`.
+#[cfg(feature = "multithreading")]
+pub(crate) fn locked_by_current_thread(raw_display: *mut xlib::Display) -> bool {
+    LOCKED_DISPLAYS.with(|displays| displays.borrow().contains(&raw_display))
+}
+
+/// Unlocks the display when dropped. See `X11Display::lock`.
+///
+/// Guard can't be sent to other threads because the display
+/// must be unlocked by the thread which locked it.
+#[cfg(feature = "multithreading")]
+#[derive(Debug)]
+pub struct DisplayLockGuard<'a> {
+    xlib_handle: &'a XlibHandle,
+    raw_display: *mut xlib::Display,
+}
+
+#[cfg(feature = "multithreading")]
+impl<'a> DisplayLockGuard<'a> {
+    /// XLockDisplay
+    pub(crate) fn new(xlib_handle: &'a XlibHandle, raw_display: *mut xlib::Display) -> Self {
+        unsafe {
+            xlib_function!(xlib_handle, XLockDisplay(None, raw_display));
+        }
+
+        LOCKED_DISPLAYS.with(|displays| displays.borrow_mut().push(raw_display));
+
+        Self {
+            xlib_handle,
+            raw_display,
+        }
+    }
+}
+
+#[cfg(feature = "multithreading")]
+impl<'a> Drop for DisplayLockGuard<'a> {
+    /// XUnlockDisplay
+    fn drop(&mut self) {
+        LOCKED_DISPLAYS.with(|displays| {
+            let mut displays = displays.borrow_mut();
+
+            if let Some(i) = displays.iter().rposition(|d| *d == self.raw_display) {
+                displays.remove(i);
+            }
+        });
+
+        unsafe {
+            xlib_function!(self.xlib_handle, XUnlockDisplay(None, self.raw_display));
+        }
+    }
+}
+
+/// Error from `XlibHandle::create_display` or
+/// `XlibHandle::create_display_named`.
+///
+/// Xlib does not report why XOpenDisplay failed, so after
+/// a failed connection this library tries to connect to the X11 server's
+/// socket itself to separate `ConnectionRefused` from `AuthorizationFailed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenDisplayError {
+    /// Display name is not in format `[protocol/][hostname]:displaynumber[.screennumber]`
+    /// or it contains a null byte.
+    InvalidName,
+    /// Display name was not given and environment variable `DISPLAY` is not set.
+    DisplayNotSet,
+    /// Connecting to the X11 server's socket failed.
+    ConnectionRefused,
+    /// X11 server's socket accepted the connection, but
+    /// the server refused the Xlib connection. Usually this means that
+    /// the client is not authorized to connect to the server.
+    AuthorizationFailed,
+}
+
+impl OpenDisplayError {
+    /// Find out why XOpenDisplay failed.
+    fn from_failed_connection(display_name: Option<&str>) -> Self {
+        let display_name = match display_name {
+            Some(name) => name.to_string(),
+            None => match env::var("DISPLAY") {
+                Ok(ref name) if !name.is_empty() => name.clone(),
+                _ => return OpenDisplayError::DisplayNotSet,
+            },
+        };
+
+        match DisplayName::parse(&display_name) {
+            Some(name) => {
+                if name.server_socket_accepts_connection() {
+                    OpenDisplayError::AuthorizationFailed
+                } else {
+                    OpenDisplayError::ConnectionRefused
+                }
+            }
+            None => OpenDisplayError::InvalidName,
+        }
+    }
+}
+
+/// Timeout for each TCP connection attempt when
+/// `OpenDisplayError` is classified.
+const TCP_CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Display name `[protocol/][hostname]:displaynumber[.screennumber]`.
+struct DisplayName<'a> {
+    protocol: Option<&'a str>,
+    /// Socket path if display name starts with `/`.
+    host: &'a str,
+    display_number: u16,
+}
+
+impl<'a> DisplayName<'a> {
+    /// Returns `None` if `name` is not a valid display name.
+    fn parse(name: &'a str) -> Option<Self> {
+        let colon_index = name.rfind(':')?;
+        let (address, number) = (&name[..colon_index], &name[colon_index + 1..]);
+
+        let mut numbers = number.splitn(2, '.');
+
+        let display_number = numbers.next()?;
+        if display_number.is_empty() || !display_number.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let display_number = display_number.parse().ok()?;
+
+        if let Some(screen_number) = numbers.next() {
+            if screen_number.is_empty() || !screen_number.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+        }
+
+        let (protocol, host) = if address.starts_with('/') {
+            (None, address)
+        } else {
+            match address.find('/') {
+                Some(i) => (Some(&address[..i]), &address[i + 1..]),
+                None => (None, address),
+            }
+        };
+
+        Some(DisplayName {
+            protocol,
+            host,
+            display_number,
+        })
+    }
+
+    /// Connect to X11 server's socket without doing X11 connection setup.
+    fn server_socket_accepts_connection(&self) -> bool {
+        if self.host.starts_with('/') {
+            return UnixStream::connect(self.host).is_ok();
+        }
+
+        let local = match self.protocol {
+            Some("unix") | Some("local") => true,
+            Some(_) => false,
+            None => self.host.is_empty() || self.host == "unix",
+        };
+
+        if local {
+            UnixStream::connect(format!("/tmp/.X11-unix/X{}", self.display_number)).is_ok()
+        } else if self.host.ends_with(':') {
+            // DECnet address
+            false
+        } else {
+            let port = match 6000u16.checked_add(self.display_number) {
+                Some(port) => port,
+                None => return false,
+            };
+
+            match (self.host, port).to_socket_addrs() {
+                Ok(mut addresses) => addresses
+                    .any(|address| TcpStream::connect_timeout(&address, TCP_CONNECT_TIMEOUT).is_ok()),
+                Err(_) => false,
+            }
+        }
+    }
+}
+
 /// Enum values from Xlib.h file.
 #[repr(i8)]
 pub enum EventsQueuedMode {
@@ -293,3 +1233,59 @@ pub enum EventsQueuedMode {
     /// Behavior equals to function `xlib::XPending`.
     QueuedAfterFlush = 2,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DisplayName;
+
+    #[test]
+    fn display_name_local() {
+        let name = DisplayName::parse(":0").unwrap();
+        assert_eq!(name.protocol, None);
+        assert_eq!(name.host, "");
+        assert_eq!(name.display_number, 0);
+
+        let name = DisplayName::parse(":1.2").unwrap();
+        assert_eq!(name.host, "");
+        assert_eq!(name.display_number, 1);
+    }
+
+    #[test]
+    fn display_name_host_and_protocol() {
+        let name = DisplayName::parse("localhost:10.0").unwrap();
+        assert_eq!(name.protocol, None);
+        assert_eq!(name.host, "localhost");
+        assert_eq!(name.display_number, 10);
+
+        let name = DisplayName::parse("tcp/example.com:3").unwrap();
+        assert_eq!(name.protocol, Some("tcp"));
+        assert_eq!(name.host, "example.com");
+        assert_eq!(name.display_number, 3);
+
+        let name = DisplayName::parse("unix/:0").unwrap();
+        assert_eq!(name.protocol, Some("unix"));
+        assert_eq!(name.host, "");
+    }
+
+    #[test]
+    fn display_name_socket_path() {
+        let name = DisplayName::parse("/private/tmp/launch-abc/org.xquartz:0").unwrap();
+        assert_eq!(name.protocol, None);
+        assert_eq!(name.host, "/private/tmp/launch-abc/org.xquartz");
+        assert_eq!(name.display_number, 0);
+    }
+
+    #[test]
+    fn display_name_decnet() {
+        let name = DisplayName::parse("node::1").unwrap();
+        assert_eq!(name.host, "node:");
+        assert_eq!(name.display_number, 1);
+    }
+
+    #[test]
+    fn display_name_invalid() {
+        for name in &["", "0", "localhost", ":", ":a", ":-1", ":0.", ":0.a", ":70000"] {
+            assert!(DisplayName::parse(name).is_none(), "{:?}", name);
+        }
+    }
+}
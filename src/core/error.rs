@@ -1,19 +1,156 @@
 //! Xlib error handling
 
-use std::io;
-use std::io::Write;
-use std::os::raw::{c_char, c_int, c_uchar, c_ulong};
-use std::sync::Mutex;
-use std::process;
+use std::os::raw::{c_char, c_int, c_uchar, c_ulong, c_void};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::CString;
 use std::mem;
+use std::ptr;
 
 use super::display::X11Display;
+#[cfg(feature = "multithreading")]
+use super::display::DisplayLockGuard;
 use super::XlibHandle;
 
+use extension::ExtensionInfo;
+
 use x11::xlib;
 
+use libc;
+
+/// Max count of errors stored for one display. Errors
+/// which do not fit in the error queue are discarded.
+pub const ERROR_QUEUE_CAPACITY: usize = 64;
+
 lazy_static! {
-    static ref ERROR_BUFFER: Mutex<Option<ErrorEvent>> = Mutex::new(None);
+    /// Error states of open displays. Key is display pointer
+    /// converted to `usize`.
+    static ref DISPLAY_ERROR_STATES: Mutex<HashMap<usize, Arc<DisplayErrorState>>> =
+        Mutex::new(HashMap::new());
+
+    /// XSetIOErrorExitHandler is available since libX11 1.7.0, so
+    /// it is loaded at runtime.
+    static ref SET_IO_ERROR_EXIT_HANDLER: Option<XSetIOErrorExitHandler> =
+        load_set_io_error_exit_handler();
+
+    static ref DIAGNOSTIC_HANDLER: Mutex<Option<Arc<DiagnosticHandler>>> = Mutex::new(None);
+}
+
+type DiagnosticHandler = dyn Fn(&Diagnostic) + Send + Sync;
+
+type XIOErrorExitHandler = unsafe extern "C" fn(*mut xlib::Display, *mut c_void);
+
+type XSetIOErrorExitHandler =
+    unsafe extern "C" fn(*mut xlib::Display, Option<XIOErrorExitHandler>, *mut c_void);
+
+/// Error state of one Xlib display connection.
+#[derive(Debug)]
+pub(crate) struct DisplayErrorState {
+    connection_lost: AtomicBool,
+    errors: Mutex<VecDeque<ErrorEvent>>,
+    overflowed_error_count: AtomicUsize,
+    /// Serial number range [first_serial; end_serial) of requests which
+    /// `check_requests` is checking. Errors in this range are stored even
+    /// if the error queue is full.
+    checked_serials: Mutex<Option<(c_ulong, c_ulong)>>,
+    /// Extensions are queried when the first extension error
+    /// or error from extension request is checked.
+    extensions: Mutex<Option<Arc<Vec<ExtensionInfo>>>>,
+}
+
+impl DisplayErrorState {
+    fn new() -> Self {
+        Self {
+            connection_lost: AtomicBool::new(false),
+            errors: Mutex::new(VecDeque::with_capacity(ERROR_QUEUE_CAPACITY)),
+            overflowed_error_count: AtomicUsize::new(0),
+            checked_serials: Mutex::new(None),
+            extensions: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn connection_lost(&self) -> bool {
+        self.connection_lost.load(Ordering::SeqCst)
+    }
+
+    fn errors(&self) -> ::std::sync::MutexGuard<'_, VecDeque<ErrorEvent>> {
+        // Xlib error handlers must not panic, so ignore mutex poisoning.
+        self.errors.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Removes errors with serial number in range [first_serial; end_serial)
+    /// from the error queue and returns the first of them.
+    fn take_request_error(&self, first_serial: c_ulong, end_serial: c_ulong) -> Option<ErrorEvent> {
+        let mut errors = self.errors();
+
+        let mut request_error = None;
+        let mut i = 0;
+
+        while i < errors.len() {
+            if errors[i].serial >= first_serial && errors[i].serial < end_serial {
+                let error = errors.remove(i);
+
+                if request_error.is_none() {
+                    request_error = error;
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        request_error
+    }
+
+    fn extensions(&self) -> ::std::sync::MutexGuard<'_, Option<Arc<Vec<ExtensionInfo>>>> {
+        self.extensions
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn checked_serials(&self) -> ::std::sync::MutexGuard<'_, Option<(c_ulong, c_ulong)>> {
+        self.checked_serials
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Adds error to the error queue if there is space for it or if the
+    /// error is caused by a request which `check_requests` is checking.
+    ///
+    /// Returns false if the error was discarded.
+    fn push_error(&self, error: ErrorEvent) -> bool {
+        let checked = match *self.checked_serials() {
+            Some((first_serial, end_serial)) => {
+                error.serial >= first_serial && error.serial < end_serial
+            }
+            None => false,
+        };
+
+        let mut errors = self.errors();
+
+        if checked || errors.len() < ERROR_QUEUE_CAPACITY {
+            errors.push_back(error);
+            true
+        } else {
+            self.overflowed_error_count.fetch_add(1, Ordering::SeqCst);
+            false
+        }
+    }
+}
+
+/// Connection to X11 server is lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLostError;
+
+/// Error from requests which were checked by waiting until
+/// X11 server has processed them.
+#[derive(Debug, Clone, Copy)]
+pub enum RequestError {
+    /// X11 server returned an error.
+    ProtocolError(ProtocolError),
+    /// Connection to X11 server is lost, so it is unknown
+    /// if X11 server processed the requests.
+    ConnectionLost,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -86,7 +223,7 @@ impl ProtocolError {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ErrorEvent {
     pub resource_id: xlib::XID,
     pub serial: c_ulong,
@@ -99,32 +236,62 @@ pub struct ErrorEvent {
 pub struct ErrorEventAndText {
     pub error: ErrorEvent,
     pub error_text: String,
+    /// Name of the failed request, for example `X_ChangeProperty` or
+    /// `RRSetCrtcConfig`.
+    pub request_name: Option<String>,
+    /// Name of the extension which the failed request belongs to.
+    pub extension_name: Option<String>,
+    /// Name of the error if the error is defined by an
+    /// extension, for example `BadRROutput`.
+    pub extension_error_name: Option<String>,
+}
+
+/// Message from this library. See `XlibHandle::set_diagnostic_handler`.
+#[derive(Debug, Clone)]
+pub enum Diagnostic {
+    /// Xlib reported a protocol error. The error is also
+    /// added to the error queue of the display.
+    ProtocolError(ErrorEvent),
+    /// Xlib reported a protocol error, but the error was discarded
+    /// because the error queue of the display was full.
+    ErrorQueueOverflow(ErrorEvent),
+    /// Xlib reported a protocol error for a display which does not
+    /// have an error queue, for example when the display is being opened or
+    /// it is already closed. The error is discarded.
+    UnregisteredDisplayError(ErrorEvent),
+    /// Xlib reported that connection to X11 server is lost.
+    ConnectionLost,
+    /// Xlib returned an unexpected value.
+    Warning(String),
+}
+
+/// See `XlibHandle::set_diagnostic_handler`.
+pub(crate) fn set_diagnostic_handler(handler: Option<Arc<DiagnosticHandler>>) {
+    *DIAGNOSTIC_HANDLER
+        .lock()
+        .unwrap_or_else(|error| error.into_inner()) = handler;
+}
+
+/// Send `diagnostic` to the diagnostic handler. If there is no
+/// handler, `diagnostic` is discarded.
+pub(crate) fn report_diagnostic(diagnostic: Diagnostic) {
+    let handler = DIAGNOSTIC_HANDLER
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .clone();
+
+    if let Some(handler) = handler {
+        handler(&diagnostic);
+    }
 }
 
 #[inline(never)]
 // Note that panics in this function will make undefined behavior, because
 // Xlib will call this function.
-// eprintln! macro may panic so write! macro is used instead.
 extern "C" fn protocol_error_handler(
-    _raw_display: *mut xlib::Display,
+    raw_display: *mut xlib::Display,
     event: *mut xlib::XErrorEvent,
 ) -> c_int {
-    let mut buffer = match ERROR_BUFFER.lock() {
-        Ok(mutex_guard) => mutex_guard,
-        Err(error) => {
-            // Abort the program because there shouldn't be any panics
-            // happening when accessing error buffer mutex.
-            let mut stderr = io::stderr();
-            let _ = write!(
-                stderr,
-                "x11_wrapper bug: error buffer mutex error {}",
-                error
-            );
-
-            process::abort();
-        }
-    };
-
     let error = unsafe {
         ErrorEvent {
             resource_id: (*event).resourceid,
@@ -135,86 +302,370 @@ extern "C" fn protocol_error_handler(
         }
     };
 
-    let mut stderr = io::stderr();
-    let _ = write!(stderr, "x11_wrapper: {:?}", error);
+    let diagnostic = match display_error_state(raw_display) {
+        Some(state) => {
+            if state.push_error(error.clone()) {
+                Diagnostic::ProtocolError(error)
+            } else {
+                Diagnostic::ErrorQueueOverflow(error)
+            }
+        }
+        None => Diagnostic::UnregisteredDisplayError(error),
+    };
+
+    report_diagnostic(diagnostic);
 
-    if buffer.is_none() {
-        *buffer = Some(error);
+    0
+}
+
+#[inline(never)]
+// Note that panics in this function will make undefined behavior, because
+// Xlib will call this function.
+//
+// If Xlib supports XSetIOErrorExitHandler, Xlib will call
+// `io_error_exit_handler` after this function returns. Otherwise
+// Xlib will terminate the program after this function returns.
+extern "C" fn io_error_handler(raw_display: *mut xlib::Display) -> c_int {
+    if let Some(state) = display_error_state(raw_display) {
+        state.connection_lost.store(true, Ordering::SeqCst);
     }
 
+    report_diagnostic(Diagnostic::ConnectionLost);
+
     0
 }
 
-// TODO: Remove eprintln!() printing.
-// TODO: Move C data type checks to XlibHandle creation.
+/// Returning from this function prevents Xlib from terminating the program.
+extern "C" fn io_error_exit_handler(_raw_display: *mut xlib::Display, _user_data: *mut c_void) {}
 
-/// XSetErrorHandler
-pub(crate) fn set_xlib_error_handler(_xlib_handle: &XlibHandle) {
+/// dlopen, dlsym
+fn load_set_io_error_exit_handler() -> Option<XSetIOErrorExitHandler> {
+    unsafe {
+        // Xlib is already loaded when this function runs, so
+        // RTLD_NOLOAD returns handle to the loaded library.
+        let library = libc::dlopen(
+            b"libX11.so.6\0".as_ptr() as *const c_char,
+            libc::RTLD_LAZY | libc::RTLD_NOLOAD,
+        );
+
+        if library.is_null() {
+            return None;
+        }
+
+        let function = libc::dlsym(library, b"XSetIOErrorExitHandler\0".as_ptr() as *const c_char);
+
+        if function.is_null() {
+            None
+        } else {
+            Some(mem::transmute::<*mut c_void, XSetIOErrorExitHandler>(function))
+        }
+    }
+}
+
+/// Returns true if Xlib supports XSetIOErrorExitHandler. If not, Xlib
+/// terminates the program when connection to X11 server is lost.
+pub fn io_error_exit_handler_available() -> bool {
+    SET_IO_ERROR_EXIT_HANDLER.is_some()
+}
+
+fn display_error_states() -> ::std::sync::MutexGuard<'static, HashMap<usize, Arc<DisplayErrorState>>> {
+    // Panics should not happen when the mutex is locked, but Xlib error handlers
+    // must not panic, so ignore mutex poisoning.
+    DISPLAY_ERROR_STATES
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+}
+
+fn display_error_state(raw_display: *mut xlib::Display) -> Option<Arc<DisplayErrorState>> {
+    display_error_states().get(&(raw_display as usize)).cloned()
+}
+
+/// Start tracking errors of `raw_display`.
+///
+/// XSetIOErrorExitHandler
+pub(crate) fn register_display(raw_display: *mut xlib::Display) -> Arc<DisplayErrorState> {
+    let state = Arc::new(DisplayErrorState::new());
+
+    display_error_states().insert(raw_display as usize, state.clone());
+
+    if let Some(set_io_error_exit_handler) = *SET_IO_ERROR_EXIT_HANDLER {
+        unsafe {
+            set_io_error_exit_handler(raw_display, Some(io_error_exit_handler), ptr::null_mut());
+        }
+    }
+
+    state
+}
+
+/// Stop tracking errors of `raw_display`. Call this after
+/// the display is closed.
+pub(crate) fn unregister_display(raw_display: *mut xlib::Display, state: &Arc<DisplayErrorState>) {
+    let mut states = display_error_states();
+
+    // Xlib may reuse the display pointer of a closed display, so
+    // check that the state belongs to the closed display.
+    let remove = match states.get(&(raw_display as usize)) {
+        Some(registered_state) => Arc::ptr_eq(registered_state, state),
+        None => false,
+    };
+
+    if remove {
+        states.remove(&(raw_display as usize));
+    }
+}
+
+/// XSetErrorHandler, XSetIOErrorHandler
+pub(crate) fn set_xlib_error_handlers(_xlib_handle: &XlibHandle) {
     unsafe {
         xlib_function!(_xlib_handle, XSetErrorHandler(None, Some(protocol_error_handler)));
+        xlib_function!(_xlib_handle, XSetIOErrorHandler(None, Some(io_error_handler)));
     }
 }
 
-/// Locks error buffer mutex and returns error buffers
-/// current value. Sets error buffer's value to `None`.
+/// Runs `send_requests`, waits until X11 server has processed the
+/// requests sent from `send_requests` and returns the first protocol
+/// error caused by those requests. Errors caused by other requests will
+/// stay in the error queue.
 ///
-/// There is only space for one error in the buffer. If there is
-/// already an error in the buffer and Xlib calls error handler
-/// function, the function will simply discard the new error.
+/// Errors caused by the checked requests are detected even if the
+/// error queue is full. Returns `RequestError::ConnectionLost` if connection
+/// to X11 server is lost.
+///
+/// If Cargo feature `multithreading` is enabled, the display is locked
+/// so that requests from other threads don't get mixed with the checked requests.
+///
+/// XNextRequest, XSync
+pub(crate) fn check_requests<T, F: FnOnce() -> T>(
+    _xlib_handle: &XlibHandle,
+    raw_display: *mut xlib::Display,
+    send_requests: F,
+) -> (T, Option<RequestError>) {
+    let state = display_error_state(raw_display);
+
+    #[cfg(feature = "multithreading")]
+    let lock = DisplayLockGuard::new(_xlib_handle, raw_display);
+
+    let first_serial = unsafe { xlib_function!(_xlib_handle, XNextRequest(Some(raw_display))) };
+
+    // Xlib may read errors from the connection while requests are sent,
+    // so the end of the range is not known yet.
+    let previous_serials = state
+        .as_ref()
+        .map(|state| state.checked_serials().replace((first_serial, c_ulong::MAX)));
+
+    let value = send_requests();
+
+    let end_serial = unsafe { xlib_function!(_xlib_handle, XNextRequest(Some(raw_display))) };
+
+    if let Some(ref state) = state {
+        *state.checked_serials() = Some((first_serial, end_serial));
+    }
+
+    unsafe {
+        xlib_function!(_xlib_handle, XSync(Some(raw_display), xlib::False));
+    }
+
+    if let Some(ref state) = state {
+        *state.checked_serials() = previous_serials.unwrap_or(None);
+    }
+
+    #[cfg(feature = "multithreading")]
+    drop(lock);
+
+    let error = match state {
+        Some(state) => match state.take_request_error(first_serial, end_serial) {
+            Some(error_event) => Some(RequestError::ProtocolError(error_event.error)),
+            None if state.connection_lost() => Some(RequestError::ConnectionLost),
+            None => None,
+        },
+        None => None,
+    };
+
+    (value, error)
+}
+
+/// Removes the oldest error from `display`'s error queue.
+///
+/// Every display has its own error queue with space
+/// for `ERROR_QUEUE_CAPACITY` errors. If the queue is full and Xlib
+/// calls error handler function, the new error is discarded.
 ///
 /// XGetErrorText
 pub fn check_error(display: &X11Display) -> Option<ErrorEventAndText> {
-    let mut buffer = ERROR_BUFFER.lock().unwrap();
-    buffer.take().map(|error_event| {
-        if mem::size_of::<c_char>() != 8 {
-            eprintln!("x11_wrapper warning: c_char is not eight bytes");
-
-            ErrorEventAndText {
-                error: error_event,
-                error_text: String::new(),
-            }
-        } else if mem::size_of::<c_uchar>() != 8 {
-            eprintln!("x11_wrapper warning: c_uchar is not eight bytes");
+    let error_event = display.error_state().errors().pop_front();
 
-            ErrorEventAndText {
-                error: error_event,
-                error_text: String::new(),
-            }
-        } else {
-            const TEXT_BUFFER_SIZE: usize = 256;
-
-            let mut text_buffer: [c_uchar; TEXT_BUFFER_SIZE] = [0; TEXT_BUFFER_SIZE];
-
-            unsafe {
-                xlib_function!(
-                    display.xlib_handle(),
-                    XGetErrorText(
-                        Some(display.raw_display()),
-                        error_event.error.to_xlib_error_code() as c_int,
-                        text_buffer.as_mut_ptr() as *mut c_char,
-                        TEXT_BUFFER_SIZE as c_int
-                    )
-                );
-            }
+    error_event.map(|error_event| add_error_text(display, error_event))
+}
 
-            // TODO: Check that last byte of the buffer is zero?
+/// Removes all errors from `display`'s error queue. Errors are
+/// ordered from oldest to newest.
+///
+/// See also documentation of `check_error`.
+///
+/// XGetErrorText
+pub fn check_errors(display: &X11Display) -> Vec<ErrorEventAndText> {
+    let error_events: Vec<ErrorEvent> = display.error_state().errors().drain(..).collect();
 
-            let mut zero_byte_index = 0;
+    error_events
+        .into_iter()
+        .map(|error_event| add_error_text(display, error_event))
+        .collect()
+}
 
-            for (i, data) in text_buffer.iter().enumerate() {
-                if *data == 0 {
-                    zero_byte_index = i;
-                }
-            }
+/// Count of errors which were discarded because `display`'s
+/// error queue was full.
+pub fn overflowed_error_count(display: &X11Display) -> usize {
+    display
+        .error_state()
+        .overflowed_error_count
+        .load(Ordering::SeqCst)
+}
+
+/// Size of text buffers for Xlib error text functions.
+const ERROR_TEXT_BUFFER_SIZE: usize = 256;
+
+/// Request codes from 128 to 255 are extension major opcodes.
+const FIRST_EXTENSION_REQUEST_CODE: c_uchar = 128;
+
+/// Error codes from 128 to 255 are extension error codes.
+const FIRST_EXTENSION_ERROR_CODE: c_uchar = 128;
+
+/// Converts text from Xlib to String.
+fn text_buffer_to_string(text_buffer: &[c_uchar]) -> String {
+    let zero_byte_index = text_buffer
+        .iter()
+        .position(|data| *data == 0)
+        .unwrap_or(text_buffer.len());
+
+    let (text, _) = text_buffer.split_at(zero_byte_index);
+
+    String::from_utf8_lossy(text).into_owned()
+}
+
+/// Returns `None` if error database does not contain the message.
+///
+/// XGetErrorDatabaseText
+fn error_database_text(display: &X11Display, name: &str, message: &str) -> Option<String> {
+    let c_name = CString::new(name).ok()?;
+    let c_message = CString::new(message).ok()?;
+    let default_string = [0 as c_char];
+
+    let mut text_buffer: [c_uchar; ERROR_TEXT_BUFFER_SIZE] = [0; ERROR_TEXT_BUFFER_SIZE];
+
+    unsafe {
+        xlib_function!(
+            display.xlib_handle(),
+            XGetErrorDatabaseText(
+                Some(display.raw_display()),
+                c_name.as_ptr(),
+                c_message.as_ptr(),
+                default_string.as_ptr(),
+                text_buffer.as_mut_ptr() as *mut c_char,
+                ERROR_TEXT_BUFFER_SIZE as c_int
+            )
+        );
+    }
+
+    let text = text_buffer_to_string(&text_buffer);
+
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Extensions of `display`. The extensions are queried only once.
+///
+/// XListExtensions, XQueryExtension, XFreeExtensionList
+fn display_extensions(display: &X11Display) -> Arc<Vec<ExtensionInfo>> {
+    let state = display.error_state();
+
+    if let Some(ref extensions) = *state.extensions() {
+        return extensions.clone();
+    }
 
-            let (text, _) = text_buffer.split_at(zero_byte_index);
+    let extensions = Arc::new(ExtensionInfo::query_all(display));
 
-            ErrorEventAndText {
-                error: error_event,
-                error_text: String::from_utf8_lossy(text).into_owned(),
+    *state.extensions() = Some(extensions.clone());
+
+    extensions
+}
+
+/// XGetErrorText, XGetErrorDatabaseText
+fn add_error_text(display: &X11Display, error_event: ErrorEvent) -> ErrorEventAndText {
+    let mut text_buffer: [c_uchar; ERROR_TEXT_BUFFER_SIZE] = [0; ERROR_TEXT_BUFFER_SIZE];
+
+    unsafe {
+        xlib_function!(
+            display.xlib_handle(),
+            XGetErrorText(
+                Some(display.raw_display()),
+                error_event.error.to_xlib_error_code() as c_int,
+                text_buffer.as_mut_ptr() as *mut c_char,
+                ERROR_TEXT_BUFFER_SIZE as c_int
+            )
+        );
+    }
+
+    let error_text = text_buffer_to_string(&text_buffer);
+
+    let mut request_name = None;
+    let mut extension_name = None;
+    let mut extension_error_name = None;
+
+    if error_event.request_code < FIRST_EXTENSION_REQUEST_CODE {
+        request_name = error_database_text(display, "XRequest", &error_event.request_code.to_string());
+    }
+
+    let error_code = error_event.error.to_xlib_error_code();
+
+    if error_event.request_code >= FIRST_EXTENSION_REQUEST_CODE
+        || error_code >= FIRST_EXTENSION_ERROR_CODE
+    {
+        let extensions = display_extensions(display);
+
+        let request_extension = extensions
+            .iter()
+            .find(|extension| extension.major_opcode == error_event.request_code as c_int);
+
+        if let Some(extension) = request_extension {
+            request_name = error_database_text(
+                display,
+                "XRequest",
+                &format!("{}.{}", extension.name, error_event.minor_code),
+            );
+            extension_name = Some(extension.name.clone());
+        }
+
+        if error_code >= FIRST_EXTENSION_ERROR_CODE {
+            // Extension with the largest first_error which is
+            // less than or equal to the error code.
+            let error_extension = extensions
+                .iter()
+                .filter(|extension| {
+                    extension.first_error != 0 && extension.first_error <= error_code as c_int
+                })
+                .max_by_key(|extension| extension.first_error);
+
+            if let Some(extension) = error_extension {
+                // Error database text is like "BadRROutput (invalid Output parameter)".
+                extension_error_name = error_database_text(
+                    display,
+                    "XProtoError",
+                    &format!("{}.{}", extension.name, error_code as c_int - extension.first_error),
+                ).and_then(|text| text.split_whitespace().next().map(|name| name.to_string()));
             }
         }
-    })
+    }
+
+    ErrorEventAndText {
+        error: error_event,
+        error_text,
+        request_name,
+        extension_name,
+        extension_error_name,
+    }
 }
 
 pub enum QueryError {
@@ -0,0 +1,134 @@
+//! Route events to window specific handlers.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use x11::xlib;
+
+use core::display::X11Display;
+use core::error::ConnectionLostError;
+
+use super::EventBuffer;
+use super::owned::OwnedEvent;
+
+type Handler = Box<dyn FnMut(&OwnedEvent)>;
+
+/// Calls the handler which is registered for the event's window.
+///
+/// Events for windows without a handler and events which are not
+/// reported to a window are given to the fallback handler.
+///
+/// Handler is unregistered automatically after DestroyNotify event of the
+/// handler's window is dispatched. Note that DestroyNotify is received only
+/// if `EventMask::STRUCTURE_NOTIFY` is selected for the window or
+/// `EventMask::SUBSTRUCTURE_NOTIFY` for its parent.
+pub struct EventDispatcher {
+    handlers: HashMap<xlib::Window, Handler>,
+    fallback: Option<Handler>,
+    event_buffer: EventBuffer,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            fallback: None,
+            event_buffer: EventBuffer::new(),
+        }
+    }
+
+    /// Register handler for events of window `window_id`. Get the id
+    /// with `Window::window_id`.
+    ///
+    /// Replaces existing handler of the window.
+    pub fn register<F: FnMut(&OwnedEvent) + 'static>(&mut self, window_id: xlib::Window, handler: F) {
+        self.handlers.insert(window_id, Box::new(handler));
+    }
+
+    /// Returns false if there was no handler for the window.
+    pub fn unregister(&mut self, window_id: xlib::Window) -> bool {
+        self.handlers.remove(&window_id).is_some()
+    }
+
+    pub fn is_registered(&self, window_id: xlib::Window) -> bool {
+        self.handlers.contains_key(&window_id)
+    }
+
+    /// Handler for events which do not have a window specific handler.
+    /// For example events from root window.
+    pub fn set_fallback<F: FnMut(&OwnedEvent) + 'static>(&mut self, handler: F) {
+        self.fallback = Some(Box::new(handler));
+    }
+
+    pub fn remove_fallback(&mut self) {
+        self.fallback = None;
+    }
+
+    /// Call handler for `event`.
+    pub fn dispatch(&mut self, event: &OwnedEvent) {
+        let handler = match event.window() {
+            Some(window_id) => self.handlers.get_mut(&window_id),
+            None => None,
+        };
+
+        match handler {
+            Some(handler) => handler(event),
+            None => {
+                if let Some(ref mut fallback) = self.fallback {
+                    fallback(event);
+                }
+            }
+        }
+
+        if let OwnedEvent::DestroyNotify { window, .. } = *event {
+            self.handlers.remove(&window);
+        }
+    }
+
+    /// Dispatch events which are available without blocking.
+    ///
+    /// Returns count of dispatched events.
+    ///
+    /// XEventsQueued, XNextEvent
+    pub fn dispatch_pending(&mut self, display: &mut X11Display) -> usize {
+        let mut count = 0;
+
+        loop {
+            let event = match display.read_event(&mut self.event_buffer) {
+                Some(event) => OwnedEvent::from(event),
+                None => return count,
+            };
+
+            self.dispatch(&event);
+            count += 1;
+        }
+    }
+
+    /// Block until event is received and dispatch it.
+    ///
+    /// Returns error if connection to X11 server is lost.
+    ///
+    /// XNextEvent
+    pub fn dispatch_blocking(&mut self, display: &mut X11Display) -> Result<(), ConnectionLostError> {
+        let event = OwnedEvent::from(display.read_event_blocking(&mut self.event_buffer)?);
+
+        self.dispatch(&event);
+
+        Ok(())
+    }
+}
+
+impl Default for EventDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for EventDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EventDispatcher")
+            .field("windows", &self.handlers.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
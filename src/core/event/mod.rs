@@ -1,26 +1,49 @@
 //! Event handling.
 
+pub mod dispatcher;
+pub mod owned;
+
+use std::any::Any;
 use std::mem;
 use std::os::raw::{c_int, c_long, c_uint};
+use std::panic::{self, AssertUnwindSafe};
 
 use x11::xlib;
 
 use super::display::X11Display;
 
+use self::owned::{NotifyDetail, NotifyMode, Rectangle};
+
 pub struct EventBuffer {
     event: xlib::XEvent,
+    /// Event was sent with `EventLoopWaker`.
+    wakeup: bool,
 }
 
 impl EventBuffer {
     pub fn new() -> Self {
         Self {
             event: unsafe { mem::zeroed() },
+            wakeup: false,
         }
     }
 
+    pub(crate) fn raw_event(&self) -> &xlib::XEvent {
+        &self.event
+    }
+
+    pub(crate) fn set_wakeup(&mut self, wakeup: bool) {
+        self.wakeup = wakeup;
+    }
+
     pub(crate) fn event_mut_ptr(&mut self) -> *mut xlib::XEvent {
         &mut self.event
     }
+
+    /// Event which was read to this buffer.
+    pub fn event(&self) -> RawEvent<'_> {
+        RawEvent::new(self)
+    }
 }
 
 pub struct RawEvent<'a> {
@@ -37,6 +60,10 @@ impl<'a> RawEvent<'a> {
     }
 
     pub fn into_event(self) -> Event<'a> {
+        if self.buffer.wakeup {
+            return Event::Wakeup;
+        }
+
         unsafe {
             let event = &self.buffer.event;
 
@@ -148,21 +175,101 @@ pub enum Event<'a> {
     SelectionRequest(&'a xlib::XSelectionRequestEvent),
     VisibilityNotify(&'a xlib::XVisibilityEvent),
 
+    /// Event from `EventLoopWaker`.
+    Wakeup,
+
     UnknownEvent(c_int),
 }
 
 impl<'a> Event<'a> {
     pub fn into_simple_event(self) -> SimpleEvent<'a> {
         match self {
-            Event::MotionNotify(e) => SimpleEvent::MotionNotify { x: e.x, y: e.y },
-            Event::ButtonPress(e) => SimpleEvent::ButtonPress { button: e.button },
-            Event::ButtonRelease(e) => SimpleEvent::ButtonRelease { button: e.button },
-            Event::KeyPress(e) => SimpleEvent::KeyPress { keycode: e.keycode },
-            Event::KeyRelease(e) => SimpleEvent::KeyRelease { keycode: e.keycode },
-            Event::EnterNotify(_) => SimpleEvent::EnterNotify,
-            Event::LeaveNotify(_) => SimpleEvent::LeaveNotify,
-            Event::FocusIn(_) => SimpleEvent::FocusIn,
-            Event::FocusOut(_) => SimpleEvent::FocusOut,
+            Event::MotionNotify(e) => SimpleEvent::MotionNotify {
+                window: e.window,
+                time: e.time,
+                x: e.x,
+                y: e.y,
+                x_root: e.x_root,
+                y_root: e.y_root,
+                modifiers: ModifierState::from_bits_truncate(e.state),
+            },
+            Event::ButtonPress(e) => SimpleEvent::ButtonPress {
+                window: e.window,
+                time: e.time,
+                button: e.button,
+                x: e.x,
+                y: e.y,
+                x_root: e.x_root,
+                y_root: e.y_root,
+                modifiers: ModifierState::from_bits_truncate(e.state),
+            },
+            Event::ButtonRelease(e) => SimpleEvent::ButtonRelease {
+                window: e.window,
+                time: e.time,
+                button: e.button,
+                x: e.x,
+                y: e.y,
+                x_root: e.x_root,
+                y_root: e.y_root,
+                modifiers: ModifierState::from_bits_truncate(e.state),
+            },
+            Event::KeyPress(e) => SimpleEvent::KeyPress {
+                window: e.window,
+                time: e.time,
+                keycode: e.keycode,
+                x: e.x,
+                y: e.y,
+                x_root: e.x_root,
+                y_root: e.y_root,
+                modifiers: ModifierState::from_bits_truncate(e.state),
+            },
+            Event::KeyRelease(e) => SimpleEvent::KeyRelease {
+                window: e.window,
+                time: e.time,
+                keycode: e.keycode,
+                x: e.x,
+                y: e.y,
+                x_root: e.x_root,
+                y_root: e.y_root,
+                modifiers: ModifierState::from_bits_truncate(e.state),
+            },
+            Event::EnterNotify(e) => SimpleEvent::EnterNotify {
+                window: e.window,
+                time: e.time,
+                x: e.x,
+                y: e.y,
+                mode: NotifyMode::from_xlib(e.mode),
+                detail: NotifyDetail::from_xlib(e.detail),
+            },
+            Event::LeaveNotify(e) => SimpleEvent::LeaveNotify {
+                window: e.window,
+                time: e.time,
+                x: e.x,
+                y: e.y,
+                mode: NotifyMode::from_xlib(e.mode),
+                detail: NotifyDetail::from_xlib(e.detail),
+            },
+            Event::FocusIn(e) => SimpleEvent::FocusIn {
+                window: e.window,
+                mode: NotifyMode::from_xlib(e.mode),
+                detail: NotifyDetail::from_xlib(e.detail),
+            },
+            Event::FocusOut(e) => SimpleEvent::FocusOut {
+                window: e.window,
+                mode: NotifyMode::from_xlib(e.mode),
+                detail: NotifyDetail::from_xlib(e.detail),
+            },
+            Event::Expose(e) => SimpleEvent::Expose {
+                window: e.window,
+                area: Rectangle {
+                    x: e.x,
+                    y: e.y,
+                    width: e.width,
+                    height: e.height,
+                },
+                count: e.count,
+            },
+            Event::DestroyNotify(e) => SimpleEvent::DestroyNotify { window: e.window },
             Event::MapNotify(_) => SimpleEvent::MapNotify,
             Event::UnmapNotify(_) => SimpleEvent::UnmapNotify,
             Event::ConfigureNotify(e) => SimpleEvent::ConfigureNotify {
@@ -172,34 +279,103 @@ impl<'a> Event<'a> {
                 height: e.height,
             },
             Event::ClientMessage(e) => SimpleEvent::ClientMessage(e),
+            Event::Wakeup => SimpleEvent::Wakeup,
             e => SimpleEvent::UnknownEvent(e),
         }
     }
 }
 
+/// Commonly used events and fields.
+///
+/// Field `modifiers` contains modifier keys and pointer buttons which were
+/// pressed before the event, `time` is the X server timestamp and
+/// `window` is the window which received the event.
 #[derive(Debug)]
 pub enum SimpleEvent<'a> {
     MotionNotify {
+        window: xlib::Window,
+        time: xlib::Time,
         x: c_int,
         y: c_int,
+        x_root: c_int,
+        y_root: c_int,
+        modifiers: ModifierState,
     },
     ButtonPress {
+        window: xlib::Window,
+        time: xlib::Time,
         button: c_uint,
+        x: c_int,
+        y: c_int,
+        x_root: c_int,
+        y_root: c_int,
+        modifiers: ModifierState,
     },
     ButtonRelease {
+        window: xlib::Window,
+        time: xlib::Time,
         button: c_uint,
+        x: c_int,
+        y: c_int,
+        x_root: c_int,
+        y_root: c_int,
+        modifiers: ModifierState,
     },
     KeyPress {
+        window: xlib::Window,
+        time: xlib::Time,
         keycode: c_uint,
+        x: c_int,
+        y: c_int,
+        x_root: c_int,
+        y_root: c_int,
+        modifiers: ModifierState,
     },
     KeyRelease {
+        window: xlib::Window,
+        time: xlib::Time,
         keycode: c_uint,
+        x: c_int,
+        y: c_int,
+        x_root: c_int,
+        y_root: c_int,
+        modifiers: ModifierState,
+    },
+    EnterNotify {
+        window: xlib::Window,
+        time: xlib::Time,
+        x: c_int,
+        y: c_int,
+        mode: NotifyMode,
+        detail: NotifyDetail,
+    },
+    LeaveNotify {
+        window: xlib::Window,
+        time: xlib::Time,
+        x: c_int,
+        y: c_int,
+        mode: NotifyMode,
+        detail: NotifyDetail,
+    },
+    FocusIn {
+        window: xlib::Window,
+        mode: NotifyMode,
+        detail: NotifyDetail,
+    },
+    FocusOut {
+        window: xlib::Window,
+        mode: NotifyMode,
+        detail: NotifyDetail,
+    },
+    Expose {
+        window: xlib::Window,
+        area: Rectangle,
+        /// Count of following Expose events.
+        count: c_int,
+    },
+    DestroyNotify {
+        window: xlib::Window,
     },
-    EnterNotify,
-    LeaveNotify,
-    FocusIn,
-    FocusOut,
-    DestroyNotify,
     MapNotify,
     UnmapNotify,
     ConfigureNotify {
@@ -209,6 +385,7 @@ pub enum SimpleEvent<'a> {
         height: c_int,
     },
     ClientMessage(&'a xlib::XClientMessageEvent),
+    Wakeup,
     UnknownEvent(Event<'a>),
 }
 
@@ -242,6 +419,26 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Modifier keys and pointer buttons which were pressed when the
+    /// event was generated.
+    pub struct ModifierState: c_uint {
+        const SHIFT = xlib::ShiftMask;
+        const LOCK = xlib::LockMask;
+        const CONTROL = xlib::ControlMask;
+        const MOD1 = xlib::Mod1Mask;
+        const MOD2 = xlib::Mod2Mask;
+        const MOD3 = xlib::Mod3Mask;
+        const MOD4 = xlib::Mod4Mask;
+        const MOD5 = xlib::Mod5Mask;
+        const BUTTON1 = xlib::Button1Mask;
+        const BUTTON2 = xlib::Button2Mask;
+        const BUTTON3 = xlib::Button3Mask;
+        const BUTTON4 = xlib::Button4Mask;
+        const BUTTON5 = xlib::Button5Mask;
+    }
+}
+
 pub trait EventCreator {
     fn raw_event_mut(&mut self) -> &mut xlib::XEvent;
 }
@@ -290,6 +487,70 @@ impl EventCreator for ClientMessageEventCreator {
     }
 }
 
+/// Rust closure for Xlib functions which take a predicate function.
+pub(crate) struct EventPredicate<F: FnMut(&xlib::XEvent) -> bool> {
+    predicate: F,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<F: FnMut(&xlib::XEvent) -> bool> EventPredicate<F> {
+    pub(crate) fn new(predicate: F) -> Self {
+        Self {
+            predicate,
+            panic: None,
+        }
+    }
+
+    /// Argument for `call`. Pointer is valid as long as `self` is
+    /// not moved or dropped.
+    pub(crate) fn as_argument(&mut self) -> xlib::XPointer {
+        self as *mut Self as xlib::XPointer
+    }
+
+    /// Predicate function for Xlib.
+    ///
+    /// Panics must not unwind through Xlib, so panic is stored and
+    /// the predicate returns true to make the Xlib function return. The
+    /// Xlib function removes that event from the event queue, so put it
+    /// back before calling `resume_panic`. After a panic, the predicate
+    /// is not called anymore and false is returned.
+    pub(crate) unsafe extern "C" fn call(
+        _display: *mut xlib::Display,
+        event: *mut xlib::XEvent,
+        argument: xlib::XPointer,
+    ) -> xlib::Bool {
+        let event_predicate = &mut *(argument as *mut Self);
+
+        if event_predicate.panic.is_some() {
+            return xlib::False;
+        }
+
+        let predicate = &mut event_predicate.predicate;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| predicate(&*event)));
+
+        match result {
+            Ok(true) => xlib::True,
+            Ok(false) => xlib::False,
+            Err(panic) => {
+                event_predicate.panic = Some(panic);
+                xlib::True
+            }
+        }
+    }
+
+    /// Returns true if the predicate panicked.
+    pub(crate) fn panicked(&self) -> bool {
+        self.panic.is_some()
+    }
+
+    /// Continue panic from the predicate if there was one.
+    pub(crate) fn resume_panic(&mut self) {
+        if let Some(panic) = self.panic.take() {
+            panic::resume_unwind(panic);
+        }
+    }
+}
+
 /// See documentation of `Display::send_event`.
 ///
 /// XSendEvent
@@ -0,0 +1,770 @@
+//! Events which do not borrow `EventBuffer`.
+
+use std::fmt;
+use std::os::raw::{c_char, c_int, c_long, c_short, c_uint, c_ulong};
+
+use x11::xlib;
+
+use core::utils::Atom;
+
+use super::{Event, ModifierState, RawEvent};
+
+/// Owned version of `Event`.
+///
+/// Window ids which can be `None` in the X11 protocol are
+/// stored as `Option<xlib::Window>`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedEvent {
+    MotionNotify(PointerEvent),
+
+    ButtonPress(ButtonEvent),
+    ButtonRelease(ButtonEvent),
+    ColormapNotify {
+        window: xlib::Window,
+        colormap: Option<xlib::Colormap>,
+        /// Colormap attribute of the window was changed.
+        new: bool,
+        installed: bool,
+    },
+    EnterNotify(CrossingEvent),
+    LeaveNotify(CrossingEvent),
+    Expose {
+        window: xlib::Window,
+        area: Rectangle,
+        /// Count of following Expose events.
+        count: c_int,
+    },
+
+    GraphicsExpose {
+        drawable: xlib::Drawable,
+        area: Rectangle,
+        count: c_int,
+        major_code: c_int,
+        minor_code: c_int,
+    },
+    NoExpose {
+        drawable: xlib::Drawable,
+        major_code: c_int,
+        minor_code: c_int,
+    },
+
+    FocusIn(FocusChangeEvent),
+    FocusOut(FocusChangeEvent),
+
+    KeymapNotify {
+        window: xlib::Window,
+        /// Bit vector of pressed keys.
+        keys: [u8; 32],
+    },
+    KeyPress(KeyEvent),
+    KeyRelease(KeyEvent),
+    PropertyNotify {
+        window: xlib::Window,
+        atom: Atom,
+        time: xlib::Time,
+        /// True if the property was deleted.
+        deleted: bool,
+    },
+    ResizeRequest {
+        window: xlib::Window,
+        width: c_int,
+        height: c_int,
+    },
+
+    CirculateNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+        on_top: bool,
+    },
+    ConfigureNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+        x: c_int,
+        y: c_int,
+        width: c_int,
+        height: c_int,
+        border_width: c_int,
+        above: Option<xlib::Window>,
+        override_redirect: bool,
+    },
+    DestroyNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+    },
+    GravityNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+        x: c_int,
+        y: c_int,
+    },
+    MapNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+        override_redirect: bool,
+    },
+    ReparentNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+        parent: xlib::Window,
+        x: c_int,
+        y: c_int,
+        override_redirect: bool,
+    },
+    UnmapNotify {
+        event: xlib::Window,
+        window: xlib::Window,
+        from_configure: bool,
+    },
+
+    CreateNotify {
+        parent: xlib::Window,
+        window: xlib::Window,
+        x: c_int,
+        y: c_int,
+        width: c_int,
+        height: c_int,
+        border_width: c_int,
+        override_redirect: bool,
+    },
+    CirculateRequest {
+        parent: xlib::Window,
+        window: xlib::Window,
+        on_top: bool,
+    },
+    ConfigureRequest {
+        parent: xlib::Window,
+        window: xlib::Window,
+        x: c_int,
+        y: c_int,
+        width: c_int,
+        height: c_int,
+        border_width: c_int,
+        above: Option<xlib::Window>,
+        /// Stack mode.
+        detail: c_int,
+        value_mask: c_ulong,
+    },
+    MapRequest {
+        parent: xlib::Window,
+        window: xlib::Window,
+    },
+
+    ClientMessage {
+        window: xlib::Window,
+        message_type: Atom,
+        data: ClientMessageData,
+    },
+    MappingNotify {
+        request: MappingRequest,
+        first_keycode: c_int,
+        count: c_int,
+    },
+    SelectionClear {
+        window: xlib::Window,
+        selection: Atom,
+        time: xlib::Time,
+    },
+    SelectionNotify {
+        requestor: xlib::Window,
+        selection: Atom,
+        target: Atom,
+        /// `None` if the conversion failed.
+        property: Option<Atom>,
+        time: xlib::Time,
+    },
+    SelectionRequest {
+        owner: xlib::Window,
+        requestor: xlib::Window,
+        selection: Atom,
+        target: Atom,
+        property: Option<Atom>,
+        time: xlib::Time,
+    },
+    VisibilityNotify {
+        window: xlib::Window,
+        state: Visibility,
+    },
+
+    /// Event from `EventLoopWaker`.
+    Wakeup,
+
+    /// Event type which does not have a variant, for
+    /// example an extension event.
+    UnknownEvent(UnknownEvent),
+}
+
+/// Event which does not have a variant in `OwnedEvent`.
+#[derive(Clone, Copy)]
+pub struct UnknownEvent {
+    event_type: c_int,
+    raw_event: Option<xlib::XEvent>,
+}
+
+impl UnknownEvent {
+    pub fn event_type(&self) -> c_int {
+        self.event_type
+    }
+
+    /// Event data for parsing extension events, for
+    /// example with `Xkb::parse_owned_event`.
+    ///
+    /// Returns `None` if the event was converted from `Event`, because
+    /// `Event::UnknownEvent` does not have the event data.
+    pub fn raw_event(&self) -> Option<&xlib::XEvent> {
+        self.raw_event.as_ref()
+    }
+}
+
+impl fmt::Debug for UnknownEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("UnknownEvent")
+            .field("event_type", &self.event_type)
+            .field("raw_event", &self.raw_event.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for UnknownEvent {
+    fn eq(&self, other: &Self) -> bool {
+        let raw_event_eq = match (self.raw_event, other.raw_event) {
+            (Some(event), Some(other_event)) => unsafe { event.pad == other_event.pad },
+            (None, None) => true,
+            _ => false,
+        };
+
+        self.event_type == other.event_type && raw_event_eq
+    }
+}
+
+/// Fields of MotionNotify event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerEvent {
+    pub window: xlib::Window,
+    pub root: xlib::Window,
+    pub subwindow: Option<xlib::Window>,
+    pub time: xlib::Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: ModifierState,
+    pub is_hint: bool,
+    pub same_screen: bool,
+}
+
+/// Fields of ButtonPress and ButtonRelease events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonEvent {
+    pub window: xlib::Window,
+    pub root: xlib::Window,
+    pub subwindow: Option<xlib::Window>,
+    pub time: xlib::Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: ModifierState,
+    pub button: c_uint,
+    pub same_screen: bool,
+}
+
+/// Fields of KeyPress and KeyRelease events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub window: xlib::Window,
+    pub root: xlib::Window,
+    pub subwindow: Option<xlib::Window>,
+    pub time: xlib::Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub state: ModifierState,
+    pub keycode: c_uint,
+    pub same_screen: bool,
+}
+
+/// Fields of EnterNotify and LeaveNotify events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrossingEvent {
+    pub window: xlib::Window,
+    pub root: xlib::Window,
+    pub subwindow: Option<xlib::Window>,
+    pub time: xlib::Time,
+    pub x: c_int,
+    pub y: c_int,
+    pub x_root: c_int,
+    pub y_root: c_int,
+    pub mode: NotifyMode,
+    pub detail: NotifyDetail,
+    pub same_screen: bool,
+    pub focus: bool,
+    pub state: ModifierState,
+}
+
+/// Fields of FocusIn and FocusOut events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusChangeEvent {
+    pub window: xlib::Window,
+    pub mode: NotifyMode,
+    pub detail: NotifyDetail,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub x: c_int,
+    pub y: c_int,
+    pub width: c_int,
+    pub height: c_int,
+}
+
+/// ClientMessage data with the event's format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientMessageData {
+    Format8([c_char; 20]),
+    Format16([c_short; 10]),
+    Format32([c_long; 5]),
+    /// Format field was not 8, 16 or 32.
+    UnknownFormat(c_int),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyMode {
+    Normal,
+    Grab,
+    Ungrab,
+    WhileGrabbed,
+    Unknown(c_int),
+}
+
+impl NotifyMode {
+    pub(crate) fn from_xlib(mode: c_int) -> Self {
+        match mode {
+            xlib::NotifyNormal => NotifyMode::Normal,
+            xlib::NotifyGrab => NotifyMode::Grab,
+            xlib::NotifyUngrab => NotifyMode::Ungrab,
+            xlib::NotifyWhileGrabbed => NotifyMode::WhileGrabbed,
+            mode => NotifyMode::Unknown(mode),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyDetail {
+    Ancestor,
+    Virtual,
+    Inferior,
+    Nonlinear,
+    NonlinearVirtual,
+    Pointer,
+    PointerRoot,
+    DetailNone,
+    Unknown(c_int),
+}
+
+impl NotifyDetail {
+    pub(crate) fn from_xlib(detail: c_int) -> Self {
+        match detail {
+            xlib::NotifyAncestor => NotifyDetail::Ancestor,
+            xlib::NotifyVirtual => NotifyDetail::Virtual,
+            xlib::NotifyInferior => NotifyDetail::Inferior,
+            xlib::NotifyNonlinear => NotifyDetail::Nonlinear,
+            xlib::NotifyNonlinearVirtual => NotifyDetail::NonlinearVirtual,
+            xlib::NotifyPointer => NotifyDetail::Pointer,
+            xlib::NotifyPointerRoot => NotifyDetail::PointerRoot,
+            xlib::NotifyDetailNone => NotifyDetail::DetailNone,
+            detail => NotifyDetail::Unknown(detail),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingRequest {
+    Modifier,
+    Keyboard,
+    Pointer,
+    Unknown(c_int),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Unobscured,
+    PartiallyObscured,
+    FullyObscured,
+    Unknown(c_int),
+}
+
+impl OwnedEvent {
+    /// Window which the event is reported to. This is the same as
+    /// field `window` of `xlib::XAnyEvent`.
+    ///
+    /// Returns `None` for events which are not reported to a window.
+    pub fn window(&self) -> Option<xlib::Window> {
+        let window = match *self {
+            OwnedEvent::MotionNotify(ref e) => e.window,
+            OwnedEvent::ButtonPress(ref e) | OwnedEvent::ButtonRelease(ref e) => e.window,
+            OwnedEvent::ColormapNotify { window, .. } => window,
+            OwnedEvent::EnterNotify(ref e) | OwnedEvent::LeaveNotify(ref e) => e.window,
+            OwnedEvent::Expose { window, .. } => window,
+            OwnedEvent::GraphicsExpose { drawable, .. } => drawable,
+            OwnedEvent::NoExpose { drawable, .. } => drawable,
+            OwnedEvent::FocusIn(ref e) | OwnedEvent::FocusOut(ref e) => e.window,
+            OwnedEvent::KeymapNotify { window, .. } => window,
+            OwnedEvent::KeyPress(ref e) | OwnedEvent::KeyRelease(ref e) => e.window,
+            OwnedEvent::PropertyNotify { window, .. } => window,
+            OwnedEvent::ResizeRequest { window, .. } => window,
+            OwnedEvent::CirculateNotify { event, .. } => event,
+            OwnedEvent::ConfigureNotify { event, .. } => event,
+            OwnedEvent::DestroyNotify { event, .. } => event,
+            OwnedEvent::GravityNotify { event, .. } => event,
+            OwnedEvent::MapNotify { event, .. } => event,
+            OwnedEvent::ReparentNotify { event, .. } => event,
+            OwnedEvent::UnmapNotify { event, .. } => event,
+            OwnedEvent::CreateNotify { parent, .. } => parent,
+            OwnedEvent::CirculateRequest { parent, .. } => parent,
+            OwnedEvent::ConfigureRequest { parent, .. } => parent,
+            OwnedEvent::MapRequest { parent, .. } => parent,
+            OwnedEvent::ClientMessage { window, .. } => window,
+            OwnedEvent::SelectionClear { window, .. } => window,
+            OwnedEvent::SelectionNotify { requestor, .. } => requestor,
+            OwnedEvent::SelectionRequest { owner, .. } => owner,
+            OwnedEvent::VisibilityNotify { window, .. } => window,
+            OwnedEvent::MappingNotify { .. }
+            | OwnedEvent::Wakeup
+            | OwnedEvent::UnknownEvent(_) => return None,
+        };
+
+        Some(window)
+    }
+}
+
+fn to_bool(value: xlib::Bool) -> bool {
+    value != xlib::False
+}
+
+fn optional_id(id: xlib::XID) -> Option<xlib::XID> {
+    if id == 0 {
+        None
+    } else {
+        Some(id)
+    }
+}
+
+fn optional_atom(atom_id: xlib::Atom) -> Option<Atom> {
+    optional_id(atom_id).map(Atom::from_raw)
+}
+
+fn on_top(place: c_int) -> bool {
+    place == xlib::PlaceOnTop
+}
+
+impl<'a> From<Event<'a>> for OwnedEvent {
+    fn from(event: Event<'a>) -> Self {
+        match event {
+            Event::MotionNotify(e) => OwnedEvent::MotionNotify(PointerEvent {
+                window: e.window,
+                root: e.root,
+                subwindow: optional_id(e.subwindow),
+                time: e.time,
+                x: e.x,
+                y: e.y,
+                x_root: e.x_root,
+                y_root: e.y_root,
+                state: ModifierState::from_bits_truncate(e.state),
+                is_hint: e.is_hint as c_int == xlib::NotifyHint,
+                same_screen: to_bool(e.same_screen),
+            }),
+            Event::ButtonPress(e) => OwnedEvent::ButtonPress(ButtonEvent::from(e)),
+            Event::ButtonRelease(e) => OwnedEvent::ButtonRelease(ButtonEvent::from(e)),
+            Event::ColormapNotify(e) => OwnedEvent::ColormapNotify {
+                window: e.window,
+                colormap: optional_id(e.colormap),
+                new: to_bool(e.new),
+                installed: e.state == xlib::ColormapInstalled,
+            },
+            Event::EnterNotify(e) => OwnedEvent::EnterNotify(CrossingEvent::from(e)),
+            Event::LeaveNotify(e) => OwnedEvent::LeaveNotify(CrossingEvent::from(e)),
+            Event::Expose(e) => OwnedEvent::Expose {
+                window: e.window,
+                area: Rectangle {
+                    x: e.x,
+                    y: e.y,
+                    width: e.width,
+                    height: e.height,
+                },
+                count: e.count,
+            },
+            Event::GraphicsExpose(e) => OwnedEvent::GraphicsExpose {
+                drawable: e.drawable,
+                area: Rectangle {
+                    x: e.x,
+                    y: e.y,
+                    width: e.width,
+                    height: e.height,
+                },
+                count: e.count,
+                major_code: e.major_code,
+                minor_code: e.minor_code,
+            },
+            Event::NoExpose(e) => OwnedEvent::NoExpose {
+                drawable: e.drawable,
+                major_code: e.major_code,
+                minor_code: e.minor_code,
+            },
+            Event::FocusIn(e) => OwnedEvent::FocusIn(FocusChangeEvent::from(e)),
+            Event::FocusOut(e) => OwnedEvent::FocusOut(FocusChangeEvent::from(e)),
+            Event::KeymapNotify(e) => {
+                let mut keys = [0; 32];
+
+                for (key, value) in keys.iter_mut().zip(e.key_vector.iter()) {
+                    *key = *value as u8;
+                }
+
+                OwnedEvent::KeymapNotify {
+                    window: e.window,
+                    keys,
+                }
+            }
+            Event::KeyPress(e) => OwnedEvent::KeyPress(KeyEvent::from(e)),
+            Event::KeyRelease(e) => OwnedEvent::KeyRelease(KeyEvent::from(e)),
+            Event::PropertyNotify(e) => OwnedEvent::PropertyNotify {
+                window: e.window,
+                atom: Atom::from_raw(e.atom),
+                time: e.time,
+                deleted: e.state != xlib::PropertyNewValue,
+            },
+            Event::ResizeRequest(e) => OwnedEvent::ResizeRequest {
+                window: e.window,
+                width: e.width,
+                height: e.height,
+            },
+            Event::CirculateNotify(e) => OwnedEvent::CirculateNotify {
+                event: e.event,
+                window: e.window,
+                on_top: on_top(e.place),
+            },
+            Event::ConfigureNotify(e) => OwnedEvent::ConfigureNotify {
+                event: e.event,
+                window: e.window,
+                x: e.x,
+                y: e.y,
+                width: e.width,
+                height: e.height,
+                border_width: e.border_width,
+                above: optional_id(e.above),
+                override_redirect: to_bool(e.override_redirect),
+            },
+            Event::DestroyNotify(e) => OwnedEvent::DestroyNotify {
+                event: e.event,
+                window: e.window,
+            },
+            Event::GravityNotify(e) => OwnedEvent::GravityNotify {
+                event: e.event,
+                window: e.window,
+                x: e.x,
+                y: e.y,
+            },
+            Event::MapNotify(e) => OwnedEvent::MapNotify {
+                event: e.event,
+                window: e.window,
+                override_redirect: to_bool(e.override_redirect),
+            },
+            Event::ReparentNotify(e) => OwnedEvent::ReparentNotify {
+                event: e.event,
+                window: e.window,
+                parent: e.parent,
+                x: e.x,
+                y: e.y,
+                override_redirect: to_bool(e.override_redirect),
+            },
+            Event::UnmapNotify(e) => OwnedEvent::UnmapNotify {
+                event: e.event,
+                window: e.window,
+                from_configure: to_bool(e.from_configure),
+            },
+            Event::CreateNotify(e) => OwnedEvent::CreateNotify {
+                parent: e.parent,
+                window: e.window,
+                x: e.x,
+                y: e.y,
+                width: e.width,
+                height: e.height,
+                border_width: e.border_width,
+                override_redirect: to_bool(e.override_redirect),
+            },
+            Event::CirculateRequest(e) => OwnedEvent::CirculateRequest {
+                parent: e.parent,
+                window: e.window,
+                on_top: on_top(e.place),
+            },
+            Event::ConfigureRequest(e) => OwnedEvent::ConfigureRequest {
+                parent: e.parent,
+                window: e.window,
+                x: e.x,
+                y: e.y,
+                width: e.width,
+                height: e.height,
+                border_width: e.border_width,
+                above: optional_id(e.above),
+                detail: e.detail,
+                value_mask: e.value_mask,
+            },
+            Event::MapRequest(e) => OwnedEvent::MapRequest {
+                parent: e.parent,
+                window: e.window,
+            },
+            Event::ClientMessage(e) => {
+                let data = match e.format {
+                    8 => {
+                        let mut bytes = [0; 20];
+                        bytes.copy_from_slice(e.data.as_bytes());
+                        ClientMessageData::Format8(bytes)
+                    }
+                    16 => {
+                        let mut shorts = [0; 10];
+                        shorts.copy_from_slice(e.data.as_shorts());
+                        ClientMessageData::Format16(shorts)
+                    }
+                    32 => {
+                        let mut longs = [0; 5];
+                        longs.copy_from_slice(e.data.as_longs());
+                        ClientMessageData::Format32(longs)
+                    }
+                    format => ClientMessageData::UnknownFormat(format),
+                };
+
+                OwnedEvent::ClientMessage {
+                    window: e.window,
+                    message_type: Atom::from_raw(e.message_type),
+                    data,
+                }
+            }
+            Event::MappingNotify(e) => OwnedEvent::MappingNotify {
+                request: match e.request {
+                    xlib::MappingModifier => MappingRequest::Modifier,
+                    xlib::MappingKeyboard => MappingRequest::Keyboard,
+                    xlib::MappingPointer => MappingRequest::Pointer,
+                    request => MappingRequest::Unknown(request),
+                },
+                first_keycode: e.first_keycode,
+                count: e.count,
+            },
+            Event::SelectionClear(e) => OwnedEvent::SelectionClear {
+                window: e.window,
+                selection: Atom::from_raw(e.selection),
+                time: e.time,
+            },
+            Event::SelectionNotify(e) => OwnedEvent::SelectionNotify {
+                requestor: e.requestor,
+                selection: Atom::from_raw(e.selection),
+                target: Atom::from_raw(e.target),
+                property: optional_atom(e.property),
+                time: e.time,
+            },
+            Event::SelectionRequest(e) => OwnedEvent::SelectionRequest {
+                owner: e.owner,
+                requestor: e.requestor,
+                selection: Atom::from_raw(e.selection),
+                target: Atom::from_raw(e.target),
+                property: optional_atom(e.property),
+                time: e.time,
+            },
+            Event::VisibilityNotify(e) => OwnedEvent::VisibilityNotify {
+                window: e.window,
+                state: match e.state {
+                    xlib::VisibilityUnobscured => Visibility::Unobscured,
+                    xlib::VisibilityPartiallyObscured => Visibility::PartiallyObscured,
+                    xlib::VisibilityFullyObscured => Visibility::FullyObscured,
+                    state => Visibility::Unknown(state),
+                },
+            },
+            Event::Wakeup => OwnedEvent::Wakeup,
+            Event::UnknownEvent(event_type) => OwnedEvent::UnknownEvent(UnknownEvent {
+                event_type,
+                raw_event: None,
+            }),
+        }
+    }
+}
+
+/// Unlike conversion from `Event`, `OwnedEvent::UnknownEvent`
+/// keeps the event data.
+impl<'a> From<RawEvent<'a>> for OwnedEvent {
+    fn from(event: RawEvent<'a>) -> Self {
+        let raw_event = *event.raw_event();
+
+        match OwnedEvent::from(event.into_event()) {
+            OwnedEvent::UnknownEvent(unknown_event) => OwnedEvent::UnknownEvent(UnknownEvent {
+                event_type: unknown_event.event_type,
+                raw_event: Some(raw_event),
+            }),
+            event => event,
+        }
+    }
+}
+
+impl<'a> From<&'a xlib::XButtonEvent> for ButtonEvent {
+    fn from(e: &'a xlib::XButtonEvent) -> Self {
+        Self {
+            window: e.window,
+            root: e.root,
+            subwindow: optional_id(e.subwindow),
+            time: e.time,
+            x: e.x,
+            y: e.y,
+            x_root: e.x_root,
+            y_root: e.y_root,
+            state: ModifierState::from_bits_truncate(e.state),
+            button: e.button,
+            same_screen: to_bool(e.same_screen),
+        }
+    }
+}
+
+impl<'a> From<&'a xlib::XKeyEvent> for KeyEvent {
+    fn from(e: &'a xlib::XKeyEvent) -> Self {
+        Self {
+            window: e.window,
+            root: e.root,
+            subwindow: optional_id(e.subwindow),
+            time: e.time,
+            x: e.x,
+            y: e.y,
+            x_root: e.x_root,
+            y_root: e.y_root,
+            state: ModifierState::from_bits_truncate(e.state),
+            keycode: e.keycode,
+            same_screen: to_bool(e.same_screen),
+        }
+    }
+}
+
+impl<'a> From<&'a xlib::XCrossingEvent> for CrossingEvent {
+    fn from(e: &'a xlib::XCrossingEvent) -> Self {
+        Self {
+            window: e.window,
+            root: e.root,
+            subwindow: optional_id(e.subwindow),
+            time: e.time,
+            x: e.x,
+            y: e.y,
+            x_root: e.x_root,
+            y_root: e.y_root,
+            mode: NotifyMode::from_xlib(e.mode),
+            detail: NotifyDetail::from_xlib(e.detail),
+            same_screen: to_bool(e.same_screen),
+            focus: to_bool(e.focus),
+            state: ModifierState::from_bits_truncate(e.state),
+        }
+    }
+}
+
+impl<'a> From<&'a xlib::XFocusChangeEvent> for FocusChangeEvent {
+    fn from(e: &'a xlib::XFocusChangeEvent) -> Self {
+        Self {
+            window: e.window,
+            mode: NotifyMode::from_xlib(e.mode),
+            detail: NotifyDetail::from_xlib(e.detail),
+        }
+    }
+}
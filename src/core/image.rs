@@ -0,0 +1,62 @@
+//! Image format information
+//!
+//! See Xlib manual section "Image Format Functions and Macros".
+
+use std::os::raw::c_int;
+
+use x11::xlib;
+
+use super::error::{QueryError, QueryResult};
+
+/// Pixmap format supported by X11 server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixmapFormat {
+    pub depth: c_int,
+    pub bits_per_pixel: c_int,
+    /// Scanline length in bits is multiple of this value.
+    pub scanline_pad: c_int,
+}
+
+impl From<xlib::XPixmapFormatValues> for PixmapFormat {
+    fn from(values: xlib::XPixmapFormatValues) -> Self {
+        Self {
+            depth: values.depth,
+            bits_per_pixel: values.bits_per_pixel,
+            scanline_pad: values.scanline_pad,
+        }
+    }
+}
+
+/// Byte order of image data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    LeastSignificantByteFirst,
+    MostSignificantByteFirst,
+}
+
+impl ByteOrder {
+    pub(crate) fn from_xlib(value: c_int) -> QueryResult<Self> {
+        match value {
+            xlib::LSBFirst => Ok(ByteOrder::LeastSignificantByteFirst),
+            xlib::MSBFirst => Ok(ByteOrder::MostSignificantByteFirst),
+            _ => Err(QueryError::UnknownEnumValue),
+        }
+    }
+}
+
+/// Bit order of bitmap units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitOrder {
+    LeastSignificantBitFirst,
+    MostSignificantBitFirst,
+}
+
+impl BitOrder {
+    pub(crate) fn from_xlib(value: c_int) -> QueryResult<Self> {
+        match value {
+            xlib::LSBFirst => Ok(BitOrder::LeastSignificantBitFirst),
+            xlib::MSBFirst => Ok(BitOrder::MostSignificantBitFirst),
+            _ => Err(QueryError::UnknownEnumValue),
+        }
+    }
+}
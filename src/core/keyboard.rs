@@ -0,0 +1,289 @@
+//! Keyboard mapping
+
+use std::os::raw::{c_int, c_void};
+use std::mem;
+use std::slice;
+
+use x11::xlib;
+
+use super::display::X11Display;
+use super::event::ModifierState;
+use super::event::owned::{MappingRequest, OwnedEvent};
+use super::keysym::Keysym;
+
+/// Count of modifiers in X11 protocol: Shift, Lock, Control and Mod1-Mod5.
+const MODIFIER_COUNT: usize = 8;
+
+/// Keycode to keysym mapping and modifier mapping of the keyboard.
+///
+/// Update the mapping with `refresh` or `refresh_owned` when
+/// `Event::MappingNotify` is received.
+#[derive(Debug, Clone)]
+pub struct KeyboardMapping {
+    min_keycode: xlib::KeyCode,
+    max_keycode: xlib::KeyCode,
+    keysyms_per_keycode: usize,
+    /// Keysyms of every keycode from `min_keycode` to `max_keycode`.
+    keysyms: Vec<xlib::KeySym>,
+    /// Keycodes for every modifier.
+    modifiers: Vec<Vec<xlib::KeyCode>>,
+}
+
+impl KeyboardMapping {
+    /// Returns error if Xlib returned null pointer.
+    ///
+    /// XDisplayKeycodes, XGetKeyboardMapping, XGetModifierMapping, XFree, XFreeModifiermap
+    pub fn new(display: &X11Display) -> Result<Self, ()> {
+        let mut min_keycode = 0;
+        let mut max_keycode = 0;
+
+        unsafe {
+            xlib_function!(
+                display.xlib_handle(),
+                XDisplayKeycodes(Some(display.raw_display()), &mut min_keycode, &mut max_keycode)
+            );
+        }
+
+        // X11 protocol limits keycodes to range [8; 255].
+        if min_keycode < 8 || max_keycode > 255 || min_keycode > max_keycode {
+            return Err(());
+        }
+
+        let mut mapping = Self {
+            min_keycode: min_keycode as xlib::KeyCode,
+            max_keycode: max_keycode as xlib::KeyCode,
+            keysyms_per_keycode: 0,
+            keysyms: vec![],
+            modifiers: vec![],
+        };
+
+        mapping.update_keysyms(display)?;
+        mapping.update_modifiers(display)?;
+
+        Ok(mapping)
+    }
+
+    /// Update the mapping after `Event::MappingNotify`. Field `display`
+    /// of `event` is ignored.
+    ///
+    /// Returns error if Xlib returned null pointer.
+    ///
+    /// XRefreshKeyboardMapping, XGetKeyboardMapping, XGetModifierMapping, XFree, XFreeModifiermap
+    pub fn refresh(&mut self, display: &X11Display, event: &xlib::XMappingEvent) -> Result<(), ()> {
+        let mut event = *event;
+        // XRefreshKeyboardMapping reads the display from the event.
+        event.display = display.raw_display();
+
+        // XRefreshKeyboardMapping updates Xlib's own keyboard
+        // mapping which is used for example in XLookupString.
+        #[cfg(feature = "multithreading")]
+        let lock = display.lock();
+
+        unsafe {
+            xlib_function!(display.xlib_handle(), XRefreshKeyboardMapping(None, &mut event));
+        }
+
+        #[cfg(feature = "multithreading")]
+        drop(lock);
+
+        match event.request {
+            xlib::MappingKeyboard => self.update_keysyms(display),
+            xlib::MappingModifier => self.update_modifiers(display),
+            _ => Ok(()),
+        }
+    }
+
+    /// Like `refresh`, but for events from `X11Display::drain_events`
+    /// and `EventDispatcher`. Events other than `OwnedEvent::MappingNotify`
+    /// are ignored.
+    ///
+    /// Returns error if Xlib returned null pointer.
+    ///
+    /// XRefreshKeyboardMapping, XGetKeyboardMapping, XGetModifierMapping, XFree, XFreeModifiermap
+    pub fn refresh_owned(&mut self, display: &X11Display, event: &OwnedEvent) -> Result<(), ()> {
+        if let OwnedEvent::MappingNotify {
+            request,
+            first_keycode,
+            count,
+        } = *event
+        {
+            let mut mapping_event: xlib::XMappingEvent = unsafe { mem::zeroed() };
+
+            mapping_event.type_ = xlib::MappingNotify;
+            mapping_event.request = match request {
+                MappingRequest::Modifier => xlib::MappingModifier,
+                MappingRequest::Keyboard => xlib::MappingKeyboard,
+                MappingRequest::Pointer => xlib::MappingPointer,
+                MappingRequest::Unknown(request) => request,
+            };
+            mapping_event.first_keycode = first_keycode;
+            mapping_event.count = count;
+
+            self.refresh(display, &mapping_event)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// XGetKeyboardMapping, XFree
+    fn update_keysyms(&mut self, display: &X11Display) -> Result<(), ()> {
+        let keycode_count = (self.max_keycode - self.min_keycode) as c_int + 1;
+        let mut keysyms_per_keycode = 0;
+
+        let keysyms_ptr = unsafe {
+            xlib_function!(
+                display.xlib_handle(),
+                XGetKeyboardMapping(
+                    Some(display.raw_display()),
+                    self.min_keycode,
+                    keycode_count,
+                    &mut keysyms_per_keycode
+                )
+            )
+        };
+
+        if keysyms_ptr.is_null() {
+            return Err(());
+        }
+
+        let keysyms_per_keycode = keysyms_per_keycode.max(0) as usize;
+        let len = keycode_count as usize * keysyms_per_keycode;
+
+        self.keysyms = unsafe { slice::from_raw_parts(keysyms_ptr, len) }.to_vec();
+        self.keysyms_per_keycode = keysyms_per_keycode;
+
+        unsafe {
+            xlib_function!(display.xlib_handle(), XFree(None, keysyms_ptr as *mut c_void));
+        }
+
+        Ok(())
+    }
+
+    /// XGetModifierMapping, XFreeModifiermap
+    fn update_modifiers(&mut self, display: &X11Display) -> Result<(), ()> {
+        let modifier_keymap = unsafe {
+            xlib_function!(display.xlib_handle(), XGetModifierMapping(Some(display.raw_display())))
+        };
+
+        if modifier_keymap.is_null() {
+            return Err(());
+        }
+
+        let keys_per_modifier = unsafe { (*modifier_keymap).max_keypermod.max(0) as usize };
+        let keycodes_ptr = unsafe { (*modifier_keymap).modifiermap };
+
+        self.modifiers = if keycodes_ptr.is_null() || keys_per_modifier == 0 {
+            vec![vec![]; MODIFIER_COUNT]
+        } else {
+            let keycodes =
+                unsafe { slice::from_raw_parts(keycodes_ptr, MODIFIER_COUNT * keys_per_modifier) };
+
+            // Zero means that there is no key in that slot.
+            keycodes
+                .chunks(keys_per_modifier)
+                .map(|keys| keys.iter().cloned().filter(|keycode| *keycode != 0).collect())
+                .collect()
+        };
+
+        unsafe {
+            xlib_function!(display.xlib_handle(), XFreeModifiermap(None, modifier_keymap));
+        }
+
+        Ok(())
+    }
+
+    /// Keycode range of the keyboard.
+    pub fn keycode_range(&self) -> (xlib::KeyCode, xlib::KeyCode) {
+        (self.min_keycode, self.max_keycode)
+    }
+
+    /// Keysyms of `keycode` without `NoSymbol` values.
+    /// Index of the list is not the same as the keysym's column index.
+    pub fn keysyms(&self, keycode: xlib::KeyCode) -> Vec<Keysym> {
+        self.raw_keysyms(keycode)
+            .iter()
+            .filter(|keysym| **keysym != xlib::NoSymbol as xlib::KeySym)
+            .map(|keysym| Keysym::from_raw(*keysym))
+            .collect()
+    }
+
+    /// Keysym at `column` of `keycode`'s keysym list.
+    ///
+    /// Returns `None` if there is no keysym.
+    pub fn keysym(&self, keycode: xlib::KeyCode, column: usize) -> Option<Keysym> {
+        match self.raw_keysyms(keycode).get(column) {
+            Some(&keysym) if keysym != xlib::NoSymbol as xlib::KeySym => Some(Keysym::from_raw(keysym)),
+            _ => None,
+        }
+    }
+
+    fn raw_keysyms(&self, keycode: xlib::KeyCode) -> &[xlib::KeySym] {
+        if keycode < self.min_keycode || keycode > self.max_keycode {
+            return &[];
+        }
+
+        let start = (keycode - self.min_keycode) as usize * self.keysyms_per_keycode;
+
+        &self.keysyms[start..start + self.keysyms_per_keycode]
+    }
+
+    /// Keycodes which are mapped to `modifier`. Returns empty list if
+    /// `modifier` is not a single modifier bit from `SHIFT` to `MOD5`.
+    pub fn modifier_keycodes(&self, modifier: ModifierState) -> &[xlib::KeyCode] {
+        match Self::modifier_index(modifier) {
+            Some(i) => &self.modifiers[i],
+            None => &[],
+        }
+    }
+
+    fn modifier_index(modifier: ModifierState) -> Option<usize> {
+        let bits = modifier.bits();
+
+        if bits.count_ones() == 1 && (bits.trailing_zeros() as usize) < MODIFIER_COUNT {
+            Some(bits.trailing_zeros() as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Modifier which has a key with one of the `keysyms`.
+    pub fn find_modifier(&self, keysyms: &[Keysym]) -> Option<ModifierState> {
+        self.modifiers
+            .iter()
+            .position(|keycodes| {
+                keycodes.iter().any(|keycode| {
+                    self.raw_keysyms(*keycode)
+                        .iter()
+                        .any(|keysym| keysyms.contains(&Keysym::from_raw(*keysym)))
+                })
+            })
+            .map(|i| ModifierState::from_bits_truncate(1 << i))
+    }
+
+    /// Modifier of NumLock key. Usually `MOD2`.
+    pub fn num_lock(&self) -> Option<ModifierState> {
+        self.find_modifier(&[Keysym::Num_Lock])
+    }
+
+    /// Modifier of Alt keys. Usually `MOD1`.
+    pub fn alt(&self) -> Option<ModifierState> {
+        self.find_modifier(&[Keysym::Alt_L, Keysym::Alt_R])
+    }
+
+    /// Modifier of Super keys (Windows logo keys). Usually `MOD4`.
+    pub fn super_key(&self) -> Option<ModifierState> {
+        self.find_modifier(&[Keysym::Super_L, Keysym::Super_R])
+    }
+
+    /// Modifier of AltGr key. Usually `MOD5`.
+    pub fn alt_gr(&self) -> Option<ModifierState> {
+        self.find_modifier(&[Keysym::ISO_Level3_Shift, Keysym::Mode_switch])
+    }
+}
+
+impl X11Display {
+    /// See `KeyboardMapping::new`.
+    pub fn keyboard_mapping(&self) -> Result<KeyboardMapping, ()> {
+        KeyboardMapping::new(self)
+    }
+}
@@ -0,0 +1,750 @@
+//! Keyboard symbols
+//!
+//! Keysym names and values are from files `keysymdef.h` and `XF86keysym.h`.
+
+use std::os::raw::{c_char, c_int, c_uint};
+use std::ptr;
+
+use x11::xlib;
+
+use super::display::X11Display;
+
+macro_rules! keysyms {
+    ( $( $name:ident = $value:expr, )* ) => {
+        /// Keyboard symbol. Variant names are the same as in Xlib without
+        /// the `XK_` prefix.
+        ///
+        /// Keysyms which do not have a variant are stored in `Other`.
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Keysym {
+            $( $name, )*
+            Other(xlib::KeySym),
+        }
+
+        impl Keysym {
+            pub fn from_raw(keysym: xlib::KeySym) -> Self {
+                match keysym {
+                    $( $value => Keysym::$name, )*
+                    keysym => Keysym::Other(keysym),
+                }
+            }
+
+            pub fn raw(&self) -> xlib::KeySym {
+                match *self {
+                    $( Keysym::$name => $value, )*
+                    Keysym::Other(keysym) => keysym,
+                }
+            }
+        }
+    };
+}
+
+keysyms! {
+    // Latin-1
+    space = 0x0020,
+    exclam = 0x0021,
+    quotedbl = 0x0022,
+    numbersign = 0x0023,
+    dollar = 0x0024,
+    percent = 0x0025,
+    ampersand = 0x0026,
+    apostrophe = 0x0027,
+    parenleft = 0x0028,
+    parenright = 0x0029,
+    asterisk = 0x002A,
+    plus = 0x002B,
+    comma = 0x002C,
+    minus = 0x002D,
+    period = 0x002E,
+    slash = 0x002F,
+    colon = 0x003A,
+    semicolon = 0x003B,
+    less = 0x003C,
+    equal = 0x003D,
+    greater = 0x003E,
+    question = 0x003F,
+    at = 0x0040,
+    A = 0x0041,
+    B = 0x0042,
+    C = 0x0043,
+    D = 0x0044,
+    E = 0x0045,
+    F = 0x0046,
+    G = 0x0047,
+    H = 0x0048,
+    I = 0x0049,
+    J = 0x004A,
+    K = 0x004B,
+    L = 0x004C,
+    M = 0x004D,
+    N = 0x004E,
+    O = 0x004F,
+    P = 0x0050,
+    Q = 0x0051,
+    R = 0x0052,
+    S = 0x0053,
+    T = 0x0054,
+    U = 0x0055,
+    V = 0x0056,
+    W = 0x0057,
+    X = 0x0058,
+    Y = 0x0059,
+    Z = 0x005A,
+    bracketleft = 0x005B,
+    backslash = 0x005C,
+    bracketright = 0x005D,
+    asciicircum = 0x005E,
+    underscore = 0x005F,
+    grave = 0x0060,
+    a = 0x0061,
+    b = 0x0062,
+    c = 0x0063,
+    d = 0x0064,
+    e = 0x0065,
+    f = 0x0066,
+    g = 0x0067,
+    h = 0x0068,
+    i = 0x0069,
+    j = 0x006A,
+    k = 0x006B,
+    l = 0x006C,
+    m = 0x006D,
+    n = 0x006E,
+    o = 0x006F,
+    p = 0x0070,
+    q = 0x0071,
+    r = 0x0072,
+    s = 0x0073,
+    t = 0x0074,
+    u = 0x0075,
+    v = 0x0076,
+    w = 0x0077,
+    x = 0x0078,
+    y = 0x0079,
+    z = 0x007A,
+    braceleft = 0x007B,
+    bar = 0x007C,
+    braceright = 0x007D,
+    asciitilde = 0x007E,
+    nobreakspace = 0x00A0,
+    exclamdown = 0x00A1,
+    cent = 0x00A2,
+    sterling = 0x00A3,
+    currency = 0x00A4,
+    yen = 0x00A5,
+    brokenbar = 0x00A6,
+    section = 0x00A7,
+    diaeresis = 0x00A8,
+    copyright = 0x00A9,
+    ordfeminine = 0x00AA,
+    guillemotleft = 0x00AB,
+    notsign = 0x00AC,
+    hyphen = 0x00AD,
+    registered = 0x00AE,
+    macron = 0x00AF,
+    degree = 0x00B0,
+    plusminus = 0x00B1,
+    twosuperior = 0x00B2,
+    threesuperior = 0x00B3,
+    acute = 0x00B4,
+    mu = 0x00B5,
+    paragraph = 0x00B6,
+    periodcentered = 0x00B7,
+    cedilla = 0x00B8,
+    onesuperior = 0x00B9,
+    masculine = 0x00BA,
+    guillemotright = 0x00BB,
+    onequarter = 0x00BC,
+    onehalf = 0x00BD,
+    threequarters = 0x00BE,
+    questiondown = 0x00BF,
+    Agrave = 0x00C0,
+    Aacute = 0x00C1,
+    Acircumflex = 0x00C2,
+    Atilde = 0x00C3,
+    Adiaeresis = 0x00C4,
+    Aring = 0x00C5,
+    AE = 0x00C6,
+    Ccedilla = 0x00C7,
+    Egrave = 0x00C8,
+    Eacute = 0x00C9,
+    Ecircumflex = 0x00CA,
+    Ediaeresis = 0x00CB,
+    Igrave = 0x00CC,
+    Iacute = 0x00CD,
+    Icircumflex = 0x00CE,
+    Idiaeresis = 0x00CF,
+    ETH = 0x00D0,
+    Ntilde = 0x00D1,
+    Ograve = 0x00D2,
+    Oacute = 0x00D3,
+    Ocircumflex = 0x00D4,
+    Otilde = 0x00D5,
+    Odiaeresis = 0x00D6,
+    multiply = 0x00D7,
+    Ooblique = 0x00D8,
+    Ugrave = 0x00D9,
+    Uacute = 0x00DA,
+    Ucircumflex = 0x00DB,
+    Udiaeresis = 0x00DC,
+    Yacute = 0x00DD,
+    THORN = 0x00DE,
+    ssharp = 0x00DF,
+    agrave = 0x00E0,
+    aacute = 0x00E1,
+    acircumflex = 0x00E2,
+    atilde = 0x00E3,
+    adiaeresis = 0x00E4,
+    aring = 0x00E5,
+    ae = 0x00E6,
+    ccedilla = 0x00E7,
+    egrave = 0x00E8,
+    eacute = 0x00E9,
+    ecircumflex = 0x00EA,
+    ediaeresis = 0x00EB,
+    igrave = 0x00EC,
+    iacute = 0x00ED,
+    icircumflex = 0x00EE,
+    idiaeresis = 0x00EF,
+    eth = 0x00F0,
+    ntilde = 0x00F1,
+    ograve = 0x00F2,
+    oacute = 0x00F3,
+    ocircumflex = 0x00F4,
+    otilde = 0x00F5,
+    odiaeresis = 0x00F6,
+    division = 0x00F7,
+    oslash = 0x00F8,
+    ugrave = 0x00F9,
+    uacute = 0x00FA,
+    ucircumflex = 0x00FB,
+    udiaeresis = 0x00FC,
+    yacute = 0x00FD,
+    thorn = 0x00FE,
+    ydiaeresis = 0x00FF,
+
+    // TTY functions, cursor control, misc functions, keypad,
+    // function keys and modifiers
+    BackSpace = 0xFF08,
+    Tab = 0xFF09,
+    Linefeed = 0xFF0A,
+    Clear = 0xFF0B,
+    Return = 0xFF0D,
+    Pause = 0xFF13,
+    Scroll_Lock = 0xFF14,
+    Sys_Req = 0xFF15,
+    Escape = 0xFF1B,
+    Delete = 0xFFFF,
+    Multi_key = 0xFF20,
+    Kanji = 0xFF21,
+    Muhenkan = 0xFF22,
+    Henkan_Mode = 0xFF23,
+    Romaji = 0xFF24,
+    Hiragana = 0xFF25,
+    Katakana = 0xFF26,
+    Hiragana_Katakana = 0xFF27,
+    Zenkaku = 0xFF28,
+    Hankaku = 0xFF29,
+    Zenkaku_Hankaku = 0xFF2A,
+    Touroku = 0xFF2B,
+    Massyo = 0xFF2C,
+    Kana_Lock = 0xFF2D,
+    Kana_Shift = 0xFF2E,
+    Eisu_Shift = 0xFF2F,
+    Eisu_toggle = 0xFF30,
+    Home = 0xFF50,
+    Left = 0xFF51,
+    Up = 0xFF52,
+    Right = 0xFF53,
+    Down = 0xFF54,
+    Prior = 0xFF55,
+    Next = 0xFF56,
+    End = 0xFF57,
+    Begin = 0xFF58,
+    Select = 0xFF60,
+    Print = 0xFF61,
+    Execute = 0xFF62,
+    Insert = 0xFF63,
+    Undo = 0xFF65,
+    Redo = 0xFF66,
+    Menu = 0xFF67,
+    Find = 0xFF68,
+    Cancel = 0xFF69,
+    Help = 0xFF6A,
+    Break = 0xFF6B,
+    Mode_switch = 0xFF7E,
+    Num_Lock = 0xFF7F,
+    KP_Space = 0xFF80,
+    KP_Tab = 0xFF89,
+    KP_Enter = 0xFF8D,
+    KP_F1 = 0xFF91,
+    KP_F2 = 0xFF92,
+    KP_F3 = 0xFF93,
+    KP_F4 = 0xFF94,
+    KP_Home = 0xFF95,
+    KP_Left = 0xFF96,
+    KP_Up = 0xFF97,
+    KP_Right = 0xFF98,
+    KP_Down = 0xFF99,
+    KP_Prior = 0xFF9A,
+    KP_Next = 0xFF9B,
+    KP_End = 0xFF9C,
+    KP_Begin = 0xFF9D,
+    KP_Insert = 0xFF9E,
+    KP_Delete = 0xFF9F,
+    KP_Equal = 0xFFBD,
+    KP_Multiply = 0xFFAA,
+    KP_Add = 0xFFAB,
+    KP_Separator = 0xFFAC,
+    KP_Subtract = 0xFFAD,
+    KP_Decimal = 0xFFAE,
+    KP_Divide = 0xFFAF,
+    KP_0 = 0xFFB0,
+    KP_1 = 0xFFB1,
+    KP_2 = 0xFFB2,
+    KP_3 = 0xFFB3,
+    KP_4 = 0xFFB4,
+    KP_5 = 0xFFB5,
+    KP_6 = 0xFFB6,
+    KP_7 = 0xFFB7,
+    KP_8 = 0xFFB8,
+    KP_9 = 0xFFB9,
+    F1 = 0xFFBE,
+    F2 = 0xFFBF,
+    F3 = 0xFFC0,
+    F4 = 0xFFC1,
+    F5 = 0xFFC2,
+    F6 = 0xFFC3,
+    F7 = 0xFFC4,
+    F8 = 0xFFC5,
+    F9 = 0xFFC6,
+    F10 = 0xFFC7,
+    F11 = 0xFFC8,
+    F12 = 0xFFC9,
+    F13 = 0xFFCA,
+    F14 = 0xFFCB,
+    F15 = 0xFFCC,
+    F16 = 0xFFCD,
+    F17 = 0xFFCE,
+    F18 = 0xFFCF,
+    F19 = 0xFFD0,
+    F20 = 0xFFD1,
+    F21 = 0xFFD2,
+    F22 = 0xFFD3,
+    F23 = 0xFFD4,
+    F24 = 0xFFD5,
+    F25 = 0xFFD6,
+    F26 = 0xFFD7,
+    F27 = 0xFFD8,
+    F28 = 0xFFD9,
+    F29 = 0xFFDA,
+    F30 = 0xFFDB,
+    F31 = 0xFFDC,
+    F32 = 0xFFDD,
+    F33 = 0xFFDE,
+    F34 = 0xFFDF,
+    F35 = 0xFFE0,
+    Shift_L = 0xFFE1,
+    Shift_R = 0xFFE2,
+    Control_L = 0xFFE3,
+    Control_R = 0xFFE4,
+    Caps_Lock = 0xFFE5,
+    Shift_Lock = 0xFFE6,
+    Meta_L = 0xFFE7,
+    Meta_R = 0xFFE8,
+    Alt_L = 0xFFE9,
+    Alt_R = 0xFFEA,
+    Super_L = 0xFFEB,
+    Super_R = 0xFFEC,
+    Hyper_L = 0xFFED,
+    Hyper_R = 0xFFEE,
+    ISO_Level3_Shift = 0xFE03,
+    ISO_Level5_Shift = 0xFE11,
+    ISO_Next_Group = 0xFE08,
+    ISO_Prev_Group = 0xFE0A,
+    ISO_Left_Tab = 0xFE20,
+
+    // XFree86 vendor specific keys
+    XF86ModeLock = 0x1008FF01,
+    XF86MonBrightnessUp = 0x1008FF02,
+    XF86MonBrightnessDown = 0x1008FF03,
+    XF86KbdLightOnOff = 0x1008FF04,
+    XF86KbdBrightnessUp = 0x1008FF05,
+    XF86KbdBrightnessDown = 0x1008FF06,
+    XF86Standby = 0x1008FF10,
+    XF86AudioLowerVolume = 0x1008FF11,
+    XF86AudioMute = 0x1008FF12,
+    XF86AudioRaiseVolume = 0x1008FF13,
+    XF86AudioPlay = 0x1008FF14,
+    XF86AudioStop = 0x1008FF15,
+    XF86AudioPrev = 0x1008FF16,
+    XF86AudioNext = 0x1008FF17,
+    XF86HomePage = 0x1008FF18,
+    XF86Mail = 0x1008FF19,
+    XF86Start = 0x1008FF1A,
+    XF86Search = 0x1008FF1B,
+    XF86AudioRecord = 0x1008FF1C,
+    XF86Calculator = 0x1008FF1D,
+    XF86Memo = 0x1008FF1E,
+    XF86ToDoList = 0x1008FF1F,
+    XF86Calendar = 0x1008FF20,
+    XF86PowerDown = 0x1008FF21,
+    XF86ContrastAdjust = 0x1008FF22,
+    XF86RockerUp = 0x1008FF23,
+    XF86RockerDown = 0x1008FF24,
+    XF86RockerEnter = 0x1008FF25,
+    XF86Back = 0x1008FF26,
+    XF86Forward = 0x1008FF27,
+    XF86Stop = 0x1008FF28,
+    XF86Refresh = 0x1008FF29,
+    XF86PowerOff = 0x1008FF2A,
+    XF86WakeUp = 0x1008FF2B,
+    XF86Eject = 0x1008FF2C,
+    XF86ScreenSaver = 0x1008FF2D,
+    XF86WWW = 0x1008FF2E,
+    XF86Sleep = 0x1008FF2F,
+    XF86Favorites = 0x1008FF30,
+    XF86AudioPause = 0x1008FF31,
+    XF86AudioMedia = 0x1008FF32,
+    XF86MyComputer = 0x1008FF33,
+    XF86VendorHome = 0x1008FF34,
+    XF86LightBulb = 0x1008FF35,
+    XF86Shop = 0x1008FF36,
+    XF86History = 0x1008FF37,
+    XF86OpenURL = 0x1008FF38,
+    XF86AddFavorite = 0x1008FF39,
+    XF86HotLinks = 0x1008FF3A,
+    XF86BrightnessAdjust = 0x1008FF3B,
+    XF86Finance = 0x1008FF3C,
+    XF86Community = 0x1008FF3D,
+    XF86AudioRewind = 0x1008FF3E,
+    XF86BackForward = 0x1008FF3F,
+    XF86Launch0 = 0x1008FF40,
+    XF86Launch1 = 0x1008FF41,
+    XF86Launch2 = 0x1008FF42,
+    XF86Launch3 = 0x1008FF43,
+    XF86Launch4 = 0x1008FF44,
+    XF86Launch5 = 0x1008FF45,
+    XF86Launch6 = 0x1008FF46,
+    XF86Launch7 = 0x1008FF47,
+    XF86Launch8 = 0x1008FF48,
+    XF86Launch9 = 0x1008FF49,
+    XF86LaunchA = 0x1008FF4A,
+    XF86LaunchB = 0x1008FF4B,
+    XF86LaunchC = 0x1008FF4C,
+    XF86LaunchD = 0x1008FF4D,
+    XF86LaunchE = 0x1008FF4E,
+    XF86LaunchF = 0x1008FF4F,
+    XF86ApplicationLeft = 0x1008FF50,
+    XF86ApplicationRight = 0x1008FF51,
+    XF86Book = 0x1008FF52,
+    XF86CD = 0x1008FF53,
+    XF86Calculater = 0x1008FF54,
+    XF86Clear = 0x1008FF55,
+    XF86Close = 0x1008FF56,
+    XF86Copy = 0x1008FF57,
+    XF86Cut = 0x1008FF58,
+    XF86Display = 0x1008FF59,
+    XF86DOS = 0x1008FF5A,
+    XF86Documents = 0x1008FF5B,
+    XF86Excel = 0x1008FF5C,
+    XF86Explorer = 0x1008FF5D,
+    XF86Game = 0x1008FF5E,
+    XF86Go = 0x1008FF5F,
+    XF86iTouch = 0x1008FF60,
+    XF86LogOff = 0x1008FF61,
+    XF86Market = 0x1008FF62,
+    XF86Meeting = 0x1008FF63,
+    XF86MenuKB = 0x1008FF65,
+    XF86MenuPB = 0x1008FF66,
+    XF86MySites = 0x1008FF67,
+    XF86New = 0x1008FF68,
+    XF86News = 0x1008FF69,
+    XF86OfficeHome = 0x1008FF6A,
+    XF86Open = 0x1008FF6B,
+    XF86Option = 0x1008FF6C,
+    XF86Paste = 0x1008FF6D,
+    XF86Phone = 0x1008FF6E,
+    XF86Q = 0x1008FF70,
+    XF86Reply = 0x1008FF72,
+    XF86Reload = 0x1008FF73,
+    XF86RotateWindows = 0x1008FF74,
+    XF86RotationPB = 0x1008FF75,
+    XF86RotationKB = 0x1008FF76,
+    XF86Save = 0x1008FF77,
+    XF86ScrollUp = 0x1008FF78,
+    XF86ScrollDown = 0x1008FF79,
+    XF86ScrollClick = 0x1008FF7A,
+    XF86Send = 0x1008FF7B,
+    XF86Spell = 0x1008FF7C,
+    XF86SplitScreen = 0x1008FF7D,
+    XF86Support = 0x1008FF7E,
+    XF86TaskPane = 0x1008FF7F,
+    XF86Terminal = 0x1008FF80,
+    XF86Tools = 0x1008FF81,
+    XF86Travel = 0x1008FF82,
+    XF86UserPB = 0x1008FF84,
+    XF86User1KB = 0x1008FF85,
+    XF86User2KB = 0x1008FF86,
+    XF86Video = 0x1008FF87,
+    XF86WheelButton = 0x1008FF88,
+    XF86Word = 0x1008FF89,
+    XF86Xfer = 0x1008FF8A,
+    XF86ZoomIn = 0x1008FF8B,
+    XF86ZoomOut = 0x1008FF8C,
+    XF86Away = 0x1008FF8D,
+    XF86Messenger = 0x1008FF8E,
+    XF86WebCam = 0x1008FF8F,
+    XF86MailForward = 0x1008FF90,
+    XF86Pictures = 0x1008FF91,
+    XF86Music = 0x1008FF92,
+    XF86Battery = 0x1008FF93,
+    XF86Bluetooth = 0x1008FF94,
+    XF86WLAN = 0x1008FF95,
+    XF86UWB = 0x1008FF96,
+    XF86AudioForward = 0x1008FF97,
+    XF86AudioRepeat = 0x1008FF98,
+    XF86AudioRandomPlay = 0x1008FF99,
+    XF86Subtitle = 0x1008FF9A,
+    XF86AudioCycleTrack = 0x1008FF9B,
+    XF86CycleAngle = 0x1008FF9C,
+    XF86FrameBack = 0x1008FF9D,
+    XF86FrameForward = 0x1008FF9E,
+    XF86Time = 0x1008FF9F,
+    XF86Select = 0x1008FFA0,
+    XF86View = 0x1008FFA1,
+    XF86TopMenu = 0x1008FFA2,
+    XF86Red = 0x1008FFA3,
+    XF86Green = 0x1008FFA4,
+    XF86Yellow = 0x1008FFA5,
+    XF86Blue = 0x1008FFA6,
+    XF86Suspend = 0x1008FFA7,
+    XF86Hibernate = 0x1008FFA8,
+    XF86TouchpadToggle = 0x1008FFA9,
+    XF86TouchpadOn = 0x1008FFB0,
+    XF86TouchpadOff = 0x1008FFB1,
+    XF86AudioMicMute = 0x1008FFB2,
+    XF86Switch_VT_1 = 0x1008FE01,
+    XF86Switch_VT_2 = 0x1008FE02,
+    XF86Switch_VT_3 = 0x1008FE03,
+    XF86Switch_VT_4 = 0x1008FE04,
+    XF86Switch_VT_5 = 0x1008FE05,
+    XF86Switch_VT_6 = 0x1008FE06,
+    XF86Switch_VT_7 = 0x1008FE07,
+    XF86Switch_VT_8 = 0x1008FE08,
+    XF86Switch_VT_9 = 0x1008FE09,
+    XF86Switch_VT_10 = 0x1008FE0A,
+    XF86Switch_VT_11 = 0x1008FE0B,
+    XF86Switch_VT_12 = 0x1008FE0C,
+    XF86Ungrab = 0x1008FE20,
+    XF86ClearGrab = 0x1008FE21,
+    XF86Next_VMode = 0x1008FE22,
+    XF86Prev_VMode = 0x1008FE23,
+    XF86LogWindowTree = 0x1008FE24,
+    XF86LogGrabInfo = 0x1008FE25,
+}
+
+/// Keysyms from 0x01000100 to 0x0110FFFF are Unicode characters.
+const UNICODE_KEYSYM_OFFSET: xlib::KeySym = 0x0100_0000;
+
+impl Keysym {
+    /// Character which the keysym produces, if any.
+    ///
+    /// Latin-1 keysyms, keypad keys producing characters and
+    /// Unicode keysyms are supported.
+    pub fn to_char(&self) -> Option<char> {
+        let keysym = self.raw();
+
+        let character = match *self {
+            Keysym::BackSpace => '\u{8}',
+            Keysym::Tab | Keysym::KP_Tab | Keysym::ISO_Left_Tab => '\t',
+            Keysym::Linefeed => '\n',
+            Keysym::Return | Keysym::KP_Enter => '\r',
+            Keysym::Escape => '\u{1b}',
+            Keysym::Delete => '\u{7f}',
+            Keysym::KP_Space => ' ',
+            Keysym::KP_Equal => '=',
+            Keysym::KP_Multiply => '*',
+            Keysym::KP_Add => '+',
+            Keysym::KP_Separator => ',',
+            Keysym::KP_Subtract => '-',
+            Keysym::KP_Decimal => '.',
+            Keysym::KP_Divide => '/',
+            Keysym::KP_0 => '0',
+            Keysym::KP_1 => '1',
+            Keysym::KP_2 => '2',
+            Keysym::KP_3 => '3',
+            Keysym::KP_4 => '4',
+            Keysym::KP_5 => '5',
+            Keysym::KP_6 => '6',
+            Keysym::KP_7 => '7',
+            Keysym::KP_8 => '8',
+            Keysym::KP_9 => '9',
+            // Latin-1 keysym values are the same as Unicode code points.
+            _ if (0x20..=0x7E).contains(&keysym) || (0xA0..=0xFF).contains(&keysym) => {
+                (keysym as u8) as char
+            }
+            _ if (0x0100_0100..=0x0110_FFFF).contains(&keysym) => {
+                return ::std::char::from_u32((keysym - UNICODE_KEYSYM_OFFSET) as u32);
+            }
+            _ => return None,
+        };
+
+        Some(character)
+    }
+}
+
+/// Result of `X11Display::lookup_key`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyLookup {
+    /// `None` if there is no keysym for the key.
+    pub keysym: Option<Keysym>,
+    /// Text which the key produces in Latin-1 encoding converted to `String`.
+    pub text: String,
+}
+
+/// Buffer size for XLookupString.
+const LOOKUP_BUFFER_SIZE: usize = 32;
+
+impl X11Display {
+    /// Keysym of `keycode` at keyboard group `group` and shift level `level`.
+    ///
+    /// Returns `None` if there is no keysym.
+    ///
+    /// XkbKeycodeToKeysym
+    pub fn keycode_to_keysym(&self, keycode: xlib::KeyCode, group: c_uint, level: c_uint) -> Option<Keysym> {
+        let keysym = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XkbKeycodeToKeysym(Some(self.raw_display()), keycode, group as c_int, level as c_int)
+            )
+        };
+
+        if keysym == xlib::NoSymbol as xlib::KeySym {
+            None
+        } else {
+            Some(Keysym::from_raw(keysym))
+        }
+    }
+
+    /// Translate key event to keysym and text using the event's modifier
+    /// state. Field `display` of `event` is ignored and this display is
+    /// used instead.
+    ///
+    /// XLookupString
+    pub fn lookup_key(&self, event: &xlib::XKeyEvent) -> KeyLookup {
+        let mut event = *event;
+        // XLookupString reads keyboard mapping from the event's display.
+        event.display = self.raw_display();
+
+        let mut buffer = [0 as c_char; LOOKUP_BUFFER_SIZE];
+        let mut keysym: xlib::KeySym = 0;
+
+        // XLookupString does not have display argument, so
+        // lock the display here.
+        #[cfg(feature = "multithreading")]
+        let _lock = self.lock();
+
+        let count = unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XLookupString(
+                    None,
+                    &mut event,
+                    buffer.as_mut_ptr(),
+                    LOOKUP_BUFFER_SIZE as c_int,
+                    &mut keysym,
+                    ptr::null_mut()
+                )
+            )
+        };
+
+        let count = (count.max(0) as usize).min(LOOKUP_BUFFER_SIZE);
+
+        KeyLookup {
+            keysym: if keysym == xlib::NoSymbol as xlib::KeySym {
+                None
+            } else {
+                Some(Keysym::from_raw(keysym))
+            },
+            text: buffer[..count].iter().map(|byte| (*byte as u8) as char).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x11::xlib;
+
+    use super::Keysym;
+
+    #[test]
+    fn keysym_raw_round_trip() {
+        let raw_values = (0..=0xFFFF)
+            .chain(0x0100_0000..=0x0100_0200)
+            .chain(0x1008_FF00..=0x1008_FFFF);
+
+        for raw in raw_values {
+            let keysym = Keysym::from_raw(raw);
+            assert_eq!(keysym.raw(), raw, "{:?}", keysym);
+        }
+    }
+
+    #[test]
+    fn keysym_other() {
+        let raw: xlib::KeySym = 0x1234_5678;
+
+        assert_eq!(Keysym::from_raw(raw), Keysym::Other(raw));
+        assert_eq!(Keysym::Other(raw).raw(), raw);
+
+        assert_eq!(Keysym::from_raw(0x61), Keysym::a);
+        assert_eq!(Keysym::from_raw(0xFFE1), Keysym::Shift_L);
+        assert_eq!(Keysym::Escape.raw(), 0xFF1B);
+    }
+
+    #[test]
+    fn latin1_to_char() {
+        assert_eq!(Keysym::space.to_char(), Some(' '));
+        assert_eq!(Keysym::a.to_char(), Some('a'));
+        assert_eq!(Keysym::udiaeresis.to_char(), Some('ü'));
+        assert_eq!(Keysym::from_raw(0xA0).to_char(), Some('\u{a0}'));
+        assert_eq!(Keysym::from_raw(0x7F).to_char(), None);
+        assert_eq!(Keysym::from_raw(0x1F).to_char(), None);
+    }
+
+    #[test]
+    fn keypad_to_char() {
+        assert_eq!(Keysym::KP_0.to_char(), Some('0'));
+        assert_eq!(Keysym::KP_9.to_char(), Some('9'));
+        assert_eq!(Keysym::KP_Add.to_char(), Some('+'));
+        assert_eq!(Keysym::KP_Decimal.to_char(), Some('.'));
+        assert_eq!(Keysym::KP_Enter.to_char(), Some('\r'));
+        assert_eq!(Keysym::KP_Home.to_char(), None);
+        assert_eq!(Keysym::KP_F1.to_char(), None);
+    }
+
+    #[test]
+    fn unicode_to_char() {
+        assert_eq!(Keysym::from_raw(0x0100_0100).to_char(), Some('\u{100}'));
+        assert_eq!(Keysym::from_raw(0x0100_20AC).to_char(), Some('€'));
+        assert_eq!(Keysym::from_raw(0x0101_F600).to_char(), Some('\u{1F600}'));
+        assert_eq!(Keysym::from_raw(0x0110_FFFF).to_char(), Some('\u{10FFFF}'));
+
+        // UTF-16 surrogates are not characters.
+        assert_eq!(Keysym::from_raw(0x0100_D800).to_char(), None);
+        assert_eq!(Keysym::from_raw(0x0100_DFFF).to_char(), None);
+
+        assert_eq!(Keysym::from_raw(0x0100_00FF).to_char(), None);
+        assert_eq!(Keysym::from_raw(0x0111_0000).to_char(), None);
+    }
+
+    #[test]
+    fn function_keys_to_char() {
+        assert_eq!(Keysym::BackSpace.to_char(), Some('\u{8}'));
+        assert_eq!(Keysym::Escape.to_char(), Some('\u{1b}'));
+        assert_eq!(Keysym::Shift_L.to_char(), None);
+        assert_eq!(Keysym::F1.to_char(), None);
+        assert_eq!(Keysym::Left.to_char(), None);
+    }
+}
@@ -7,14 +7,22 @@ pub mod color;
 pub mod display;
 pub mod event;
 pub mod error;
+pub mod image;
+pub mod keyboard;
+pub mod keysym;
 pub mod screen;
+#[cfg(feature = "async")]
+pub mod stream;
 pub mod visual;
+#[cfg(feature = "multithreading")]
+pub mod waker;
 pub mod utils;
 
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::fmt;
 
-use self::display::X11Display;
+use self::display::{OpenDisplayError, X11Display};
+use self::error::Diagnostic;
 
 lazy_static! {
     static ref INIT_FLAG: Mutex<bool> = Mutex::new(false);
@@ -45,6 +53,11 @@ pub struct XlibHandle {
     pub(crate) functions: ::std::sync::Arc<::x11::xlib::Xlib>,
     #[cfg(not(feature = "multithreading"))]
     pub(crate) functions: ::std::rc::Rc<::x11::xlib::Xlib>,
+
+    #[cfg(all(feature = "xlib-xcb", feature = "multithreading"))]
+    pub(crate) xlib_xcb_functions: ::std::sync::Arc<::x11::xlib_xcb::Xlib_xcb>,
+    #[cfg(all(feature = "xlib-xcb", not(feature = "multithreading")))]
+    pub(crate) xlib_xcb_functions: ::std::rc::Rc<::x11::xlib_xcb::Xlib_xcb>,
 }
 
 impl fmt::Debug for XlibHandle {
@@ -70,15 +83,31 @@ impl XlibHandle {
         #[cfg(not(feature = "multithreading"))]
         let functions = std::rc::Rc::new(functions);
 
+        #[cfg(feature = "xlib-xcb")]
+        let xlib_xcb_functions = {
+            let xlib_xcb_functions = ::x11::xlib_xcb::Xlib_xcb::open()
+                .map_err(|e| XlibInitError::LibraryLoadingError(e.detail().to_string()))?;
+
+            #[cfg(feature = "multithreading")]
+            let xlib_xcb_functions = std::sync::Arc::new(xlib_xcb_functions);
+
+            #[cfg(not(feature = "multithreading"))]
+            let xlib_xcb_functions = std::rc::Rc::new(xlib_xcb_functions);
+
+            xlib_xcb_functions
+        };
+
         Ok(XlibHandle {
-            functions
+            functions,
+            #[cfg(feature = "xlib-xcb")]
+            xlib_xcb_functions,
         })
     }
 
     /// Initialize Xlib. This function will return error if
     /// `XlibHandle` is already created.
     ///
-    /// XSetErrorHandler
+    /// XSetErrorHandler, XSetIOErrorHandler
     ///
     /// If Cargo feature `multithreading` is enabled function
     /// XInitThreads is also called.
@@ -99,7 +128,7 @@ impl XlibHandle {
                 }
             }
 
-            error::set_xlib_error_handler(&xlib_handle);
+            error::set_xlib_error_handlers(&xlib_handle);
 
             *guard = true;
 
@@ -107,11 +136,38 @@ impl XlibHandle {
         }
     }
 
-    /// Create new connection to X11 server.
+    /// Set function which receives diagnostic messages like protocol errors
+    /// and warnings from this library. This library does not write
+    /// anything to stderr, so without a handler the messages are discarded.
+    ///
+    /// Xlib error handlers call `handler`, so `handler` must not call
+    /// Xlib functions or panic. If `handler` panics, the program will abort.
+    pub fn set_diagnostic_handler<F>(&self, handler: F)
+    where
+        F: Fn(&Diagnostic) + Send + Sync + 'static,
+    {
+        error::set_diagnostic_handler(Some(Arc::new(handler)));
+    }
+
+    /// Remove the current diagnostic handler.
+    pub fn remove_diagnostic_handler(&self) {
+        error::set_diagnostic_handler(None);
+    }
+
+    /// Create new connection to X11 server which is set
+    /// with environment variable `DISPLAY`.
+    ///
+    /// XOpenDisplay
+    pub fn create_display(&self) -> Result<X11Display, OpenDisplayError> {
+        X11Display::new(self.clone(), None)
+    }
+
+    /// Create new connection to X11 server `display_name`, for
+    /// example `":99"` or `"localhost:1.0"`.
     ///
     /// XOpenDisplay
-    pub fn create_display(&self) -> Result<X11Display, ()> {
-        X11Display::new(self.clone())
+    pub fn create_display_named(&self, display_name: &str) -> Result<X11Display, OpenDisplayError> {
+        X11Display::new(self.clone(), Some(display_name))
     }
 }
 
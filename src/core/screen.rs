@@ -5,6 +5,7 @@ use x11::xlib;
 use super::color::DefaultColormap;
 use super::display::X11Display;
 use super::error::{QueryError, QueryResult};
+use super::image::PixmapFormat;
 use super::visual::Visual;
 use super::event::{send_event, ClientMessageEventCreator, EventMask};
 use super::XlibHandle;
@@ -84,6 +85,18 @@ impl Screen {
         }
     }
 
+    /// Pixmap format of the screen's default depth.
+    ///
+    /// XDefaultDepthOfScreen, XListPixmapFormats, XFree
+    pub fn default_pixmap_format(&self) -> Option<PixmapFormat> {
+        let depth = self.default_depth();
+
+        self.display_handle
+            .pixmap_formats()
+            .into_iter()
+            .find(|format| format.depth == depth)
+    }
+
     /// XDoesBackingStore
     pub fn does_backing_store(&self) -> QueryResult<BackingStore> {
         let result = unsafe {
@@ -0,0 +1,234 @@
+//! Asynchronous event stream
+//!
+//! Enabled with crate feature `async`.
+
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+
+use futures_core::Stream;
+
+use libc;
+
+use super::display::{EventsQueuedMode, X11Display};
+use super::event::owned::OwnedEvent;
+use super::event::EventBuffer;
+
+/// Stream of events from `X11Display`.
+///
+/// Xlib event queue is checked every time the stream is polled, so
+/// events which Xlib has already read from the connection are not lost.
+/// If the queue is empty, a helper thread waits until the connection's file
+/// descriptor is readable and then wakes the task.
+///
+/// Every stream which has returned `Poll::Pending` has its own helper
+/// thread. The thread stops when the stream is dropped.
+///
+/// Events which another clone of the `X11Display` reads from the
+/// connection to Xlib's event queue do not make the file descriptor
+/// readable. The queue is checked after the task's waker is registered, but
+/// if other threads read events while the stream is waiting, the stream
+/// does not wake until the next event arrives. Don't read events from other
+/// clones of the display while using the stream.
+///
+/// Stream ends when connection to X11 server is lost.
+///
+/// XFlush, XEventsQueued, XNextEvent
+#[derive(Debug)]
+pub struct EventStream {
+    // Poller thread must stop before the display
+    // closes the file descriptor.
+    poller: Option<Poller>,
+    display: X11Display,
+}
+
+impl EventStream {
+    pub fn new(display: X11Display) -> Self {
+        Self {
+            poller: None,
+            display,
+        }
+    }
+
+    pub fn display(&self) -> &X11Display {
+        &self.display
+    }
+
+    /// XNextEvent
+    fn read_queued_event(&mut self) -> Poll<Option<OwnedEvent>> {
+        let mut event_buffer = EventBuffer::new();
+
+        match self.display.read_event_blocking(&mut event_buffer) {
+            Ok(event) => Poll::Ready(Some(OwnedEvent::from(event))),
+            Err(_) => Poll::Ready(None),
+        }
+    }
+}
+
+impl Stream for EventStream {
+    type Item = OwnedEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<OwnedEvent>> {
+        if self.display.flush_output_buffer().is_err() {
+            return Poll::Ready(None);
+        }
+
+        if self.display.events_queued(EventsQueuedMode::QueuedAfterReading) > 0 {
+            return self.read_queued_event();
+        }
+
+        if self.display.connection_lost() {
+            return Poll::Ready(None);
+        }
+
+        // Event queue is now empty and all data from the connection is read,
+        // so the file descriptor becomes readable when a new event arrives.
+        let fd = self.display.connection_number();
+
+        if self.poller.is_none() {
+            match Poller::new(fd) {
+                Ok(poller) => self.poller = Some(poller),
+                Err(()) => return Poll::Ready(None),
+            }
+        }
+
+        if let Some(ref poller) = self.poller {
+            poller.wait_readable(cx.waker().clone());
+        }
+
+        // Another thread may have read events to the event queue
+        // after the queue was checked. Those events don't make the
+        // file descriptor readable.
+        if self.display.events_queued(EventsQueuedMode::QueuedAlready) > 0 {
+            return self.read_queued_event();
+        }
+
+        Poll::Pending
+    }
+}
+
+impl X11Display {
+    /// See `EventStream` documentation.
+    pub fn into_event_stream(self) -> EventStream {
+        EventStream::new(self)
+    }
+}
+
+#[derive(Debug, Default)]
+struct PollerState {
+    waker: Option<Waker>,
+    stop: bool,
+}
+
+type SharedPollerState = Arc<(Mutex<PollerState>, Condvar)>;
+
+/// Thread which waits until the connection's file descriptor is readable.
+#[derive(Debug)]
+struct Poller {
+    state: SharedPollerState,
+    stop_sender: Option<UnixStream>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Poller {
+    /// Returns error if creating a socket pair or the thread failed.
+    fn new(fd: c_int) -> Result<Self, ()> {
+        let (stop_sender, stop_receiver) = UnixStream::pair().map_err(|_| ())?;
+
+        let state: SharedPollerState = Arc::new((Mutex::new(PollerState::default()), Condvar::new()));
+        let thread_state = state.clone();
+
+        let thread = thread::Builder::new()
+            .name("x11-wrapper-event-stream".to_string())
+            .spawn(move || poller_thread(fd, stop_receiver, thread_state))
+            .map_err(|_| ())?;
+
+        Ok(Self {
+            state,
+            stop_sender: Some(stop_sender),
+            thread: Some(thread),
+        })
+    }
+
+    /// Wake `waker` when the file descriptor is readable.
+    fn wait_readable(&self, waker: Waker) {
+        lock_state(&self.state).waker = Some(waker);
+        self.state.1.notify_one();
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        lock_state(&self.state).stop = true;
+        self.state.1.notify_one();
+
+        // Closing the socket wakes the thread if it is polling.
+        self.stop_sender.take();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn lock_state(state: &SharedPollerState) -> MutexGuard<'_, PollerState> {
+    match state.0.lock() {
+        Ok(guard) => guard,
+        Err(error) => error.into_inner(),
+    }
+}
+
+fn poller_thread(fd: c_int, stop_receiver: UnixStream, state: SharedPollerState) {
+    loop {
+        {
+            let mut guard = lock_state(&state);
+
+            while guard.waker.is_none() && !guard.stop {
+                guard = match state.1.wait(guard) {
+                    Ok(guard) => guard,
+                    Err(error) => error.into_inner(),
+                };
+            }
+
+            if guard.stop {
+                return;
+            }
+        }
+
+        let mut poll_fds = [
+            libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: stop_receiver.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+
+        let result = unsafe { libc::poll(poll_fds.as_mut_ptr(), poll_fds.len() as libc::nfds_t, -1) };
+
+        if result < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+
+        if poll_fds[1].revents != 0 {
+            return;
+        }
+
+        // Wake also if poll failed, so that the stream
+        // checks the connection state.
+        if result < 0 || poll_fds[0].revents != 0 {
+            if let Some(waker) = lock_state(&state).waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_int, c_void};
 use std::mem;
 use std::ptr;
 use std::slice;
+use std::sync::Mutex;
 
 use x11::xlib;
 
@@ -194,41 +196,54 @@ impl Drop for Text {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AtomNameError {
-    UnknownCharacter,
-    /// There was internal Null byte in the string.
-    NulError,
+    /// Character is not part of the Host Portable Character Encoding.
+    /// Field `index` is the byte index of the character in the string.
+    UnsupportedCharacter { character: char, index: usize },
 }
 
-/// Characters [a-zA-Z0-9_] with ASCII encoding.
+/// Characters from the Host Portable Character Encoding with ASCII encoding.
 ///
-/// TODO: Is Host Portable Character Encoding about the same as ASCII?
-/// TODO: Support all characters from Host Portable Character Encoding
+/// Supported characters are `a-z`, `A-Z`, `0-9`,
+/// ``!"#%&'()*+,-./:;<=>?_``, space, tab and newline.
 pub struct AtomName(CString);
 
 impl AtomName {
-    /// Returns error if there were unexpected characters in the string.
+    /// Returns error if there were unsupported characters in the string.
     pub fn new(string: String) -> Result<Self, AtomNameError> {
-        for c in string.chars() {
-            match c {
-                'a'...'z' | 'A'...'Z' | '0'...'9' | '_' => (),
-                _ => return Err(AtomNameError::UnknownCharacter),
+        for (index, character) in string.char_indices() {
+            if !Self::is_supported_character(character) {
+                return Err(AtomNameError::UnsupportedCharacter { character, index });
             }
         }
 
-        match CString::new(string) {
-            Ok(c_string) => Ok(AtomName(c_string)),
-            Err(_) => Err(AtomNameError::NulError),
-        }
+        // There shouldn't be any null bytes because
+        // null is not a supported character.
+        Ok(AtomName(CString::new(string).unwrap()))
+    }
+
+    fn is_supported_character(character: char) -> bool {
+        matches!(
+            character,
+            'a'..='z' | 'A'..='Z' | '0'..='9' |
+            '!' | '"' | '#' | '%' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | '-' | '.' | '/' |
+            ':' | ';' | '<' | '=' | '>' | '?' | '_' | ' ' | '\t' | '\n'
+        )
     }
 
     fn as_ptr(&mut self) -> *const c_char {
         self.0.as_ptr()
     }
+
+    pub fn as_str(&self) -> &str {
+        // AtomName contains only ASCII characters.
+        self.0.to_str().unwrap()
+    }
 }
 
 #[repr(C)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Atom {
     // Atom does not require display handle because atoms
     // exists in X server until there is no connections to X server.
@@ -241,12 +256,20 @@ impl Atom {
     /// If `only_if_exists` is `False`, new atom will be created if there isn't an
     /// atom matching `atom_name`.
     ///
+    /// Atoms are cached in `X11Display::atom_cache`, so there is
+    /// a round trip to the X server only when the atom is not in the cache.
+    /// Use `X11Display::intern_atoms` for querying multiple atoms at once.
+    ///
     /// XInternAtom
     pub fn new(
         display: &X11Display,
         mut atom_name: AtomName,
         only_if_exists: bool,
     ) -> Result<Atom, ()> {
+        if let Some(atom) = display.atom_cache().get_atom(atom_name.as_str()) {
+            return Ok(atom);
+        }
+
         let only_if_exists = if only_if_exists {
             xlib::True
         } else {
@@ -263,12 +286,21 @@ impl Atom {
         if atom_id == 0 {
             Err(())
         } else {
-            Ok(Atom { atom_id })
+            let atom = Atom { atom_id };
+            display.atom_cache().insert(atom_name.as_str().to_string(), atom);
+            Ok(atom)
         }
     }
 
+    /// Name is queried from the X server only if it is not in
+    /// `X11Display::atom_cache`.
+    ///
     /// XGetAtomName, XFree
     pub fn get_name(&self, display: &X11Display) -> Result<String, ()> {
+        if let Some(name) = display.atom_cache().get_name(*self) {
+            return Ok(name);
+        }
+
         let text_ptr = unsafe {
             xlib_function!(
                 display.xlib_handle(),
@@ -288,6 +320,8 @@ impl Atom {
                 xlib_function!(display.xlib_handle(), XFree(None, text_ptr as *mut c_void));
             }
 
+            display.atom_cache().insert(name.clone(), *self);
+
             Ok(name)
         }
     }
@@ -302,6 +336,187 @@ impl Atom {
     }
 }
 
+macro_rules! predefined_atoms {
+    ( $( $name:ident = $xlib_name:ident, )* ) => {
+        /// Predefined atoms from file `Xatom.h`.
+        ///
+        /// These atoms exist in every X server, so using them does not
+        /// require a round trip to the X server.
+        impl Atom {
+            $(
+                pub const $name: Atom = Atom { atom_id: xlib::$xlib_name };
+            )*
+        }
+    };
+}
+
+predefined_atoms! {
+    PRIMARY = XA_PRIMARY,
+    SECONDARY = XA_SECONDARY,
+    ARC = XA_ARC,
+    ATOM = XA_ATOM,
+    BITMAP = XA_BITMAP,
+    CARDINAL = XA_CARDINAL,
+    COLORMAP = XA_COLORMAP,
+    CURSOR = XA_CURSOR,
+    CUT_BUFFER0 = XA_CUT_BUFFER0,
+    CUT_BUFFER1 = XA_CUT_BUFFER1,
+    CUT_BUFFER2 = XA_CUT_BUFFER2,
+    CUT_BUFFER3 = XA_CUT_BUFFER3,
+    CUT_BUFFER4 = XA_CUT_BUFFER4,
+    CUT_BUFFER5 = XA_CUT_BUFFER5,
+    CUT_BUFFER6 = XA_CUT_BUFFER6,
+    CUT_BUFFER7 = XA_CUT_BUFFER7,
+    DRAWABLE = XA_DRAWABLE,
+    FONT = XA_FONT,
+    INTEGER = XA_INTEGER,
+    PIXMAP = XA_PIXMAP,
+    POINT = XA_POINT,
+    RECTANGLE = XA_RECTANGLE,
+    RESOURCE_MANAGER = XA_RESOURCE_MANAGER,
+    RGB_COLOR_MAP = XA_RGB_COLOR_MAP,
+    RGB_BEST_MAP = XA_RGB_BEST_MAP,
+    RGB_BLUE_MAP = XA_RGB_BLUE_MAP,
+    RGB_DEFAULT_MAP = XA_RGB_DEFAULT_MAP,
+    RGB_GRAY_MAP = XA_RGB_GRAY_MAP,
+    RGB_GREEN_MAP = XA_RGB_GREEN_MAP,
+    RGB_RED_MAP = XA_RGB_RED_MAP,
+    STRING = XA_STRING,
+    VISUALID = XA_VISUALID,
+    WINDOW = XA_WINDOW,
+    WM_COMMAND = XA_WM_COMMAND,
+    WM_HINTS = XA_WM_HINTS,
+    WM_CLIENT_MACHINE = XA_WM_CLIENT_MACHINE,
+    WM_ICON_NAME = XA_WM_ICON_NAME,
+    WM_ICON_SIZE = XA_WM_ICON_SIZE,
+    WM_NAME = XA_WM_NAME,
+    WM_NORMAL_HINTS = XA_WM_NORMAL_HINTS,
+    WM_SIZE_HINTS = XA_WM_SIZE_HINTS,
+    WM_ZOOM_HINTS = XA_WM_ZOOM_HINTS,
+    MIN_SPACE = XA_MIN_SPACE,
+    NORM_SPACE = XA_NORM_SPACE,
+    MAX_SPACE = XA_MAX_SPACE,
+    END_SPACE = XA_END_SPACE,
+    SUPERSCRIPT_X = XA_SUPERSCRIPT_X,
+    SUPERSCRIPT_Y = XA_SUPERSCRIPT_Y,
+    SUBSCRIPT_X = XA_SUBSCRIPT_X,
+    SUBSCRIPT_Y = XA_SUBSCRIPT_Y,
+    UNDERLINE_POSITION = XA_UNDERLINE_POSITION,
+    UNDERLINE_THICKNESS = XA_UNDERLINE_THICKNESS,
+    STRIKEOUT_ASCENT = XA_STRIKEOUT_ASCENT,
+    STRIKEOUT_DESCENT = XA_STRIKEOUT_DESCENT,
+    ITALIC_ANGLE = XA_ITALIC_ANGLE,
+    X_HEIGHT = XA_X_HEIGHT,
+    QUAD_WIDTH = XA_QUAD_WIDTH,
+    WEIGHT = XA_WEIGHT,
+    POINT_SIZE = XA_POINT_SIZE,
+    RESOLUTION = XA_RESOLUTION,
+    COPYRIGHT = XA_COPYRIGHT,
+    NOTICE = XA_NOTICE,
+    FONT_NAME = XA_FONT_NAME,
+    FAMILY_NAME = XA_FAMILY_NAME,
+    FULL_NAME = XA_FULL_NAME,
+    CAP_HEIGHT = XA_CAP_HEIGHT,
+    WM_CLASS = XA_WM_CLASS,
+    WM_TRANSIENT_FOR = XA_WM_TRANSIENT_FOR,
+}
+
+/// Maps atom names to atoms and atoms to names.
+///
+/// Every `X11Display` has its own cache. Atoms are added to the cache when
+/// they are queried with `Atom::new`, `Atom::get_name` or
+/// `X11Display::intern_atoms`.
+#[derive(Debug, Default)]
+pub struct AtomCache {
+    maps: Mutex<AtomCacheMaps>,
+}
+
+#[derive(Debug, Default)]
+struct AtomCacheMaps {
+    atoms: HashMap<String, Atom>,
+    names: HashMap<Atom, String>,
+}
+
+impl AtomCache {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get cached atom. This does not make a round trip to the X server.
+    pub fn get_atom(&self, name: &str) -> Option<Atom> {
+        self.lock_maps().atoms.get(name).cloned()
+    }
+
+    /// Get cached atom name. This does not make a round trip to the X server.
+    pub fn get_name(&self, atom: Atom) -> Option<String> {
+        self.lock_maps().names.get(&atom).cloned()
+    }
+
+    pub(crate) fn insert(&self, name: String, atom: Atom) {
+        let mut maps = self.lock_maps();
+        maps.names.insert(atom, name.clone());
+        maps.atoms.insert(name, atom);
+    }
+
+    /// Query atoms which are not in the cache with one XInternAtoms call.
+    ///
+    /// Returned list has an item for every name in `atom_names`. The item is
+    /// `None` if the atom does not exist and `only_if_exists` is `true`.
+    ///
+    /// XInternAtoms
+    pub(crate) fn intern_atoms(
+        &self,
+        display: &X11Display,
+        atom_names: &[AtomName],
+        only_if_exists: bool,
+    ) -> Vec<Option<Atom>> {
+        let missing_names: Vec<&AtomName> = atom_names
+            .iter()
+            .filter(|name| self.get_atom(name.as_str()).is_none())
+            .collect();
+
+        if !missing_names.is_empty() {
+            let mut name_ptrs: Vec<*mut c_char> = missing_names
+                .iter()
+                .map(|name| name.0.as_ptr() as *mut c_char)
+                .collect();
+            let mut atom_ids: Vec<xlib::Atom> = vec![0; name_ptrs.len()];
+
+            // TODO: check that name count fits in c_int
+            unsafe {
+                xlib_function!(
+                    display.xlib_handle(),
+                    XInternAtoms(
+                        Some(display.raw_display()),
+                        name_ptrs.as_mut_ptr(),
+                        name_ptrs.len() as c_int,
+                        to_xlib_bool(only_if_exists),
+                        atom_ids.as_mut_ptr()
+                    )
+                );
+            }
+
+            for (name, atom_id) in missing_names.iter().zip(atom_ids) {
+                if atom_id != 0 {
+                    self.insert(name.as_str().to_string(), Atom::from_raw(atom_id));
+                }
+            }
+        }
+
+        atom_names
+            .iter()
+            .map(|name| self.get_atom(name.as_str()))
+            .collect()
+    }
+
+    fn lock_maps(&self) -> ::std::sync::MutexGuard<'_, AtomCacheMaps> {
+        match self.maps.lock() {
+            Ok(maps) => maps,
+            Err(error) => error.into_inner(),
+        }
+    }
+}
+
 /// Max list length is `std::os::raw::c_int::max_value()`.
 ///
 /// In C language, minimum requirement for int type is 16 bits, so
@@ -345,3 +560,42 @@ pub(crate) fn to_xlib_bool(value: bool) -> xlib::Bool {
         xlib::False
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AtomName, AtomNameError};
+
+    #[test]
+    fn atom_name_supported_characters() {
+        let name = "WM_PROTOCOLS azAZ09 !\"#%&'()*+,-./:;<=>?_\t\n";
+        assert_eq!(AtomName::new(name.to_string()).unwrap().as_str(), name);
+        assert!(AtomName::new(String::new()).is_ok());
+    }
+
+    #[test]
+    fn atom_name_unsupported_characters() {
+        for (name, character, index) in &[
+            ("_NET_WM_NAME\0", '\0', 12),
+            ("a$b", '$', 1),
+            ("@", '@', 0),
+            ("[]", '[', 0),
+            ("a\\b", '\\', 1),
+            ("a`", '`', 1),
+            ("{}", '{', 0),
+            ("~", '~', 0),
+            ("^", '^', 0),
+            ("a|", '|', 1),
+            ("\r", '\r', 0),
+            ("äö", 'ä', 0),
+            ("aä", 'ä', 1),
+        ] {
+            assert_eq!(
+                AtomName::new(name.to_string()).err(),
+                Some(AtomNameError::UnsupportedCharacter {
+                    character: *character,
+                    index: *index,
+                })
+            );
+        }
+    }
+}
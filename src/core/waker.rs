@@ -0,0 +1,122 @@
+//! Wake a thread which is waiting for events.
+//!
+//! Enabled with crate feature `multithreading`.
+
+use std::os::raw::c_uint;
+use std::ptr;
+
+use x11::xlib;
+
+use super::display::X11Display;
+use super::event::{send_event, ClientMessageEventCreator, EventMask};
+use super::utils::{Atom, AtomName};
+
+const WAKEUP_ATOM_NAME: &str = "_X11_WRAPPER_WAKEUP";
+
+/// Destination of wakeup events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WakerTarget {
+    /// Unmapped InputOnly window.
+    pub(crate) window_id: xlib::Window,
+    pub(crate) message_type: Atom,
+}
+
+impl WakerTarget {
+    /// Returns true if `event` is a wakeup event for this target.
+    pub(crate) fn matches(&self, event: &xlib::XEvent) -> bool {
+        unsafe {
+            event.type_ == xlib::ClientMessage
+                && event.client_message.window == self.window_id
+                && event.client_message.message_type == self.message_type.atom_id()
+        }
+    }
+
+    /// Creates hidden window which receives the wakeup events.
+    ///
+    /// The window is destroyed when the display is closed.
+    ///
+    /// XInternAtom, XCreateWindow
+    pub(crate) fn new(display: &X11Display) -> Result<Self, ()> {
+        let name = AtomName::new(WAKEUP_ATOM_NAME.to_string()).map_err(|_| ())?;
+        let message_type = Atom::new(display, name, false)?;
+
+        let root_window_id = display.default_screen().root_window_id().ok_or(())?;
+
+        let window_id = unsafe {
+            xlib_function!(
+                display.xlib_handle(),
+                XCreateWindow(
+                    Some(display.raw_display()),
+                    root_window_id,
+                    -1,
+                    -1,
+                    1,
+                    1,
+                    0,
+                    xlib::CopyFromParent,
+                    xlib::InputOnly as c_uint,
+                    xlib::CopyFromParent as *mut xlib::Visual,
+                    0,
+                    ptr::null_mut()
+                )
+            )
+        };
+
+        if window_id == 0 {
+            Err(())
+        } else {
+            Ok(Self {
+                window_id,
+                message_type,
+            })
+        }
+    }
+}
+
+/// Makes a thread waiting in `X11Display::read_event_blocking` return
+/// `Event::Wakeup`.
+///
+/// Create with `X11Display::create_waker`.
+#[derive(Debug, Clone)]
+pub struct EventLoopWaker {
+    display: X11Display,
+    target: WakerTarget,
+}
+
+impl EventLoopWaker {
+    pub(crate) fn new(display: X11Display, target: WakerTarget) -> Self {
+        Self { display, target }
+    }
+
+    /// Sends a wakeup event to the display's event queue and flushes the
+    /// output buffer.
+    ///
+    /// Returns error if event conversion to wire protocol format failed
+    /// or connection to X11 server is lost.
+    ///
+    /// XSendEvent, XFlush
+    pub fn wake(&self) -> Result<(), ()> {
+        let mut event = ClientMessageEventCreator::new();
+
+        {
+            let event = event.client_message_mut();
+            event.window = self.target.window_id;
+            event.message_type = self.target.message_type.atom_id();
+            event.format = 32;
+        }
+
+        // Event with empty event mask is sent to the
+        // client which created the window.
+        send_event(
+            &self.display,
+            self.target.window_id,
+            false,
+            EventMask::empty(),
+            &mut event,
+        )?;
+
+        self.display.flush_output_buffer().map_err(|_| ())?;
+
+        Ok(())
+    }
+}
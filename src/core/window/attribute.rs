@@ -4,6 +4,7 @@ use std::os::raw::{c_int, c_long, c_ulong};
 
 use x11::xlib;
 
+use core::error::{report_diagnostic, Diagnostic};
 use core::event::EventMask;
 use core::utils::XLIB_NONE;
 
@@ -214,10 +215,10 @@ impl AttributeConversions for Gravity {
             xlib::ForgetGravity => Gravity::Forget,
             xlib::StaticGravity => Gravity::Static,
             value => {
-                eprintln!(
-                    "x11_wrapper warning: unknown gravity value {}, using default value",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown gravity value {}, using default value",
                     value
-                );
+                )));
                 Gravity::default()
             }
         }
@@ -276,10 +277,10 @@ impl AttributeConversions for WindowGravity {
             xlib::SouthEastGravity => WindowGravity::SouthEast,
             xlib::UnmapGravity => WindowGravity::Unmap,
             value => {
-                eprintln!(
-                    "x11_wrapper warning: unknown window gravity value {}, using default value",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown window gravity value {}, using default value",
                     value
-                );
+                )));
                 WindowGravity::default()
             }
         }
@@ -310,10 +311,10 @@ impl AttributeConversions for BackingStore {
             xlib::WhenMapped => BackingStore::WhenMapped,
             xlib::Always => BackingStore::Always,
             value => {
-                eprintln!(
-                    "x11_wrapper warning: unknown backing store value {}, using default value",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown backing store value {}, using default value",
                     value
-                );
+                )));
                 BackingStore::default()
             }
         }
@@ -345,10 +346,10 @@ impl AttributeConversions for SaveUnder {
             xlib::True => SaveUnder(true),
             xlib::False => SaveUnder(false),
             value => {
-                eprintln!(
-                    "x11_wrapper warning: unknown save under value {}, using default value",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown save under value {}, using default value",
                     value
-                );
+                )));
                 SaveUnder::default()
             }
         }
@@ -372,10 +373,10 @@ impl AttributeConversions for EventMask {
         match EventMask::from_bits(value) {
             Some(events) => events,
             None => {
-                eprintln!(
-                    "x11_wrapper warning: unknown bits in event mask {:#b}",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown bits in event mask {:#b}",
                     value
-                );
+                )));
                 EventMask::from_bits_truncate(value)
             }
         }
@@ -409,10 +410,10 @@ impl AttributeConversions for DoNotPropagateMask {
         match DoNotPropagateMask::from_bits(value) {
             Some(events) => events,
             None => {
-                eprintln!(
-                    "x11_wrapper warning: unknown bits in 'do not propagate' mask {:#b}",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown bits in 'do not propagate' mask {:#b}",
                     value
-                );
+                )));
                 DoNotPropagateMask::from_bits_truncate(value)
             }
         }
@@ -438,10 +439,10 @@ impl AttributeConversions for OverrideRedirect {
             xlib::True => OverrideRedirect(true),
             xlib::False => OverrideRedirect(false),
             value => {
-                eprintln!(
-                    "x11_wrapper warning: unknown override redirect value {}, using default value",
+                report_diagnostic(Diagnostic::Warning(format!(
+                    "unknown override redirect value {}, using default value",
                     value
-                );
+                )));
                 OverrideRedirect::default()
             }
         }
@@ -9,6 +9,8 @@ use super::{Selection, Window, WindowProperties};
 
 use core::display::X11Display;
 use core::color::{ColormapID, CreatedColormap};
+use core::error::{check_requests, RequestError};
+use core::utils::XLIB_NONE;
 use core::visual::Visual;
 use core::screen::Screen;
 use core::XlibHandle;
@@ -85,7 +87,8 @@ impl_traits!(
 );
 
 impl InputOutputWindowBuilder<BuildTopLevelWindow> {
-    /// Parent of created window will be root window of `Screen`.
+    /// Parent of created window will be root window of `Screen`. Any
+    /// screen from `X11Display::screens` can be used.
     ///
     /// Returns error if `Screen` does not support `Visual` or `Screen`'s root window
     /// is not found.
@@ -197,6 +200,66 @@ impl TopLevelInputOutputWindow {
         self
     }
 
+    /// Like `map_window`, but waits until X11 server has processed
+    /// the request and returns the protocol error caused by it.
+    ///
+    /// XMapWindow, XSync
+    pub fn map_window_checked(&self) -> Result<(), RequestError> {
+        let ((), error) = check_requests(self.xlib_handle(), self.raw_display(), || unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XMapWindow(Some(self.display_handle.raw_display()), self.window_id)
+            );
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `unmap_window`, but waits until X11 server has processed
+    /// the request and returns the protocol error caused by it.
+    ///
+    /// XUnmapWindow, XSync
+    pub fn unmap_window_checked(&self) -> Result<(), RequestError> {
+        let ((), error) = check_requests(self.xlib_handle(), self.raw_display(), || unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XUnmapWindow(Some(self.display_handle.raw_display()), self.window_id)
+            );
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Destroy the window and return the protocol error caused
+    /// by the destroy request. Dropping the window destroys the window
+    /// without checking errors.
+    ///
+    /// XDestroyWindow, XSync
+    pub fn destroy_checked(mut self) -> Result<(), RequestError> {
+        let window_id = self.window_id;
+
+        // Window is destroyed only once, so Drop must not destroy it.
+        self.window_id = XLIB_NONE;
+
+        let ((), error) = check_requests(self.xlib_handle(), self.raw_display(), || unsafe {
+            xlib_function!(
+                self.xlib_handle(),
+                XDestroyWindow(Some(self.display_handle.raw_display()), window_id)
+            );
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
     /// XIconifyWindow
     pub fn iconify(&mut self, screen: &Screen) -> Result<(), ()> {
         unsafe {
@@ -241,6 +304,10 @@ impl TopLevelInputOutputWindow {
 impl Drop for TopLevelInputOutputWindow {
     /// XDestroyWindow - BadWindow
     fn drop(&mut self) {
+        if self.window_id == XLIB_NONE {
+            return;
+        }
+
         unsafe {
             // TODO: check errors
             xlib_function!(
@@ -12,6 +12,7 @@ use std::ptr;
 use x11::xlib;
 
 use self::input_output::TopLevelInputOutputWindow;
+use core::error::{check_requests, report_diagnostic, Diagnostic, ProtocolError, RequestError};
 use core::screen::Screen;
 use core::utils::{to_xlib_bool, Atom, AtomList, Text, TextError, XLIB_NONE};
 use core::XlibHandle;
@@ -117,6 +118,53 @@ impl ReconfigureWindow<TopLevelInputOutputWindow> {
             Ok(self.window)
         }
     }
+
+    /// Like `configure`, but waits until X11 server has processed
+    /// the requests and returns the protocol error caused by them.
+    ///
+    /// XReconfigureWMWindow, XSync
+    pub fn configure_checked(
+        mut self,
+        screen: &Screen,
+    ) -> Result<TopLevelInputOutputWindow, ConfigureError<TopLevelInputOutputWindow>> {
+        let (status, error) = {
+            let window = &self.window;
+            let window_changes = &mut self.window_changes;
+            let value_mask = self.value_mask;
+
+            check_requests(window.xlib_handle(), window.raw_display(), || unsafe {
+                xlib_function!(
+                    window.xlib_handle(),
+                    XReconfigureWMWindow(
+                        Some(window.raw_display()),
+                        window.window_id(),
+                        screen.screen_number(),
+                        value_mask.bits(),
+                        window_changes
+                    )
+                )
+            })
+        };
+
+        match (status, error) {
+            (_, Some(RequestError::ProtocolError(error))) => {
+                Err(ConfigureError::ProtocolError(self.window, error))
+            }
+            (_, Some(RequestError::ConnectionLost)) => Err(ConfigureError::ConnectionLost(self.window)),
+            (0, None) => Err(ConfigureError::FunctionFailed(self.window)),
+            (_, None) => Ok(self.window),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigureError<W> {
+    /// Xlib function returned failure status.
+    FunctionFailed(W),
+    /// X11 server returned an error.
+    ProtocolError(W, ProtocolError),
+    /// Connection to X11 server is lost.
+    ConnectionLost(W),
 }
 
 bitflags! {
@@ -442,7 +490,9 @@ pub trait WindowProperties: Window {
         }
 
         if num_prop < 0 {
-            eprintln!("x11_wrapper warning: property count is negative, returning empty AtomList");
+            report_diagnostic(Diagnostic::Warning(
+                "property count is negative, returning empty AtomList".to_string(),
+            ));
             return atom_list;
         }
 
@@ -510,6 +560,49 @@ pub trait WindowProperties: Window {
         Ok(())
     }
 
+    /// Like `change_property`, but waits until X11 server has processed
+    /// the request and returns the protocol error caused by it.
+    ///
+    /// XChangeProperty, XSync
+    fn change_property_checked(
+        &self,
+        property: Property,
+        mode: ChangePropertyMode,
+    ) -> Result<(), ChangePropertyError> {
+        // Check data length before sending anything, so that
+        // XSync is not called if the request is not sent.
+        property
+            .to_xlib_change_property_nelements()
+            .map_err(|()| ChangePropertyError::TooMuchData)?;
+
+        let (result, error) = check_requests(self.xlib_handle(), self.raw_display(), || {
+            self.change_property(property, mode)
+        });
+
+        result.map_err(|()| ChangePropertyError::TooMuchData)?;
+
+        match error {
+            Some(RequestError::ProtocolError(error)) => Err(ChangePropertyError::ProtocolError(error)),
+            Some(RequestError::ConnectionLost) => Err(ChangePropertyError::ConnectionLost),
+            None => Ok(()),
+        }
+    }
+
+    /// Like `delete_property`, but waits until X11 server has processed
+    /// the request and returns the protocol error caused by it.
+    ///
+    /// XDeleteProperty, XSync
+    fn delete_property_checked(&self, property_name: Atom) -> Result<(), RequestError> {
+        let ((), error) = check_requests(self.xlib_handle(), self.raw_display(), || {
+            self.delete_property(property_name)
+        });
+
+        match error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
     /// Set properties with type `TEXT`.
     ///
     /// XSetTextProperty
@@ -573,6 +666,16 @@ pub enum TextPropertyError {
     XlibFunctionFailed,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub enum ChangePropertyError {
+    /// Property data length is larger than c_int.
+    TooMuchData,
+    /// X11 server returned an error.
+    ProtocolError(ProtocolError),
+    /// Connection to X11 server is lost.
+    ConnectionLost,
+}
+
 #[derive(Debug)]
 pub enum PropertyError {
     DoesNotExist,
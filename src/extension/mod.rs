@@ -1,2 +1,97 @@
+//! X11 extensions
 
-//! X11 extensions
\ No newline at end of file
+pub mod xkb;
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_int;
+use std::slice;
+
+use core::display::X11Display;
+
+/// Protocol extension information from X11 server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionInfo {
+    pub name: String,
+    /// Request code of the extension's requests.
+    pub major_opcode: c_int,
+    /// Zero if the extension does not have events.
+    pub first_event: c_int,
+    /// Zero if the extension does not have errors.
+    pub first_error: c_int,
+}
+
+impl ExtensionInfo {
+    /// Returns `None` if X11 server does not support the extension
+    /// or `name` contains a null byte.
+    ///
+    /// XQueryExtension
+    pub(crate) fn query(display: &X11Display, name: &str) -> Option<Self> {
+        let c_name = CString::new(name).ok()?;
+
+        let mut major_opcode = 0;
+        let mut first_event = 0;
+        let mut first_error = 0;
+
+        let supported = unsafe {
+            xlib_function!(
+                display.xlib_handle(),
+                XQueryExtension(
+                    Some(display.raw_display()),
+                    c_name.as_ptr(),
+                    &mut major_opcode,
+                    &mut first_event,
+                    &mut first_error
+                )
+            )
+        };
+
+        if supported == 0 {
+            None
+        } else {
+            Some(Self {
+                name: name.to_string(),
+                major_opcode,
+                first_event,
+                first_error,
+            })
+        }
+    }
+
+    /// Information of every extension supported by X11 server.
+    ///
+    /// XListExtensions, XQueryExtension, XFreeExtensionList
+    pub(crate) fn query_all(display: &X11Display) -> Vec<Self> {
+        let mut count = 0;
+
+        let name_list = unsafe {
+            xlib_function!(
+                display.xlib_handle(),
+                XListExtensions(Some(display.raw_display()), &mut count)
+            )
+        };
+
+        if name_list.is_null() {
+            return vec![];
+        }
+
+        let names: Vec<String> = if count > 0 {
+            let name_ptrs = unsafe { slice::from_raw_parts(name_list, count as usize) };
+
+            name_ptrs
+                .iter()
+                .map(|name_ptr| unsafe { CStr::from_ptr(*name_ptr) }.to_string_lossy().into_owned())
+                .collect()
+        } else {
+            vec![]
+        };
+
+        unsafe {
+            xlib_function!(display.xlib_handle(), XFreeExtensionList(None, name_list));
+        }
+
+        names
+            .iter()
+            .filter_map(|name| Self::query(display, name))
+            .collect()
+    }
+}
@@ -0,0 +1,404 @@
+//! X Keyboard Extension (XKB)
+//!
+//! Keyboard groups, layout names and detectable autorepeat.
+
+use std::os::raw::{c_int, c_uint, c_ulong};
+use std::mem;
+
+use x11::xlib;
+
+use core::display::X11Display;
+use core::event::ModifierState;
+use core::event::owned::OwnedEvent;
+use core::utils::{to_xlib_bool, Atom};
+
+/// XKB version which this library supports.
+const XKB_MAJOR_VERSION: c_int = 1;
+const XKB_MINOR_VERSION: c_int = 0;
+
+/// Device specifier of the core keyboard. Missing from crate `x11`.
+const XKB_USE_CORE_KBD: c_uint = 0x0100;
+
+/// Component mask of `XkbGetNames` for group names. Missing from crate `x11`.
+const XKB_GROUP_NAMES_MASK: c_uint = 1 << 12;
+
+bitflags! {
+    /// XKB events which can be selected with `Xkb::select_events`.
+    pub struct XkbEventMask: c_ulong {
+        const NEW_KEYBOARD_NOTIFY = xlib::XkbNewKeyboardNotifyMask;
+        const STATE_NOTIFY = xlib::XkbStateNotifyMask;
+    }
+}
+
+/// Keyboard state of the core keyboard.
+///
+/// Groups are keyboard layouts. Effective group is
+/// the group which is currently used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XkbState {
+    /// Effective group.
+    pub group: c_int,
+    pub base_group: c_int,
+    pub latched_group: c_int,
+    pub locked_group: c_int,
+    /// Effective modifiers.
+    pub modifiers: ModifierState,
+    pub base_modifiers: ModifierState,
+    pub latched_modifiers: ModifierState,
+    pub locked_modifiers: ModifierState,
+}
+
+impl XkbState {
+    fn from_state_rec(state: &xlib::XkbStateRec) -> Self {
+        Self {
+            group: state.group as c_int,
+            // Base and latched groups are signed values in XKB protocol.
+            base_group: state.base_group as i16 as c_int,
+            latched_group: state.latched_group as i16 as c_int,
+            locked_group: state.locked_group as c_int,
+            modifiers: ModifierState::from_bits_truncate(state.mods as c_uint),
+            base_modifiers: ModifierState::from_bits_truncate(state.base_mods as c_uint),
+            latched_modifiers: ModifierState::from_bits_truncate(state.latched_mods as c_uint),
+            locked_modifiers: ModifierState::from_bits_truncate(state.locked_mods as c_uint),
+        }
+    }
+
+    fn from_event(event: &xlib::XkbStateNotifyEvent) -> Self {
+        Self {
+            group: event.group,
+            base_group: event.base_group,
+            latched_group: event.latched_group,
+            locked_group: event.locked_group,
+            modifiers: ModifierState::from_bits_truncate(event.mods),
+            base_modifiers: ModifierState::from_bits_truncate(event.base_mods),
+            latched_modifiers: ModifierState::from_bits_truncate(event.latched_mods),
+            locked_modifiers: ModifierState::from_bits_truncate(event.locked_mods),
+        }
+    }
+}
+
+/// XKB event. Xlib reports every XKB event with the same event type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XkbEvent {
+    /// Keyboard state changed. For example the group
+    /// changed or a modifier key was pressed.
+    StateNotify {
+        device: c_int,
+        state: XkbState,
+        /// Changed state components. See `xlib::XkbGroupStateMask` and
+        /// other state component masks.
+        changed: c_uint,
+    },
+    /// Keyboard was replaced or its keycode range changed.
+    /// Query group names again.
+    NewKeyboardNotify {
+        device: c_int,
+        old_device: c_int,
+        min_keycode: c_int,
+        max_keycode: c_int,
+    },
+    /// XKB event type which does not have a variant.
+    Other(c_int),
+}
+
+/// XKB extension of a display.
+///
+/// Create with `X11Display::xkb`.
+#[derive(Debug, Clone)]
+pub struct Xkb {
+    display: X11Display,
+    major_version: c_int,
+    minor_version: c_int,
+    major_opcode: c_int,
+    first_event: c_int,
+    first_error: c_int,
+}
+
+impl Xkb {
+    /// Returns `None` if X11 server does not support XKB or
+    /// server's XKB version is not compatible with Xlib.
+    ///
+    /// XkbQueryExtension
+    pub fn new(display: &X11Display) -> Option<Self> {
+        let mut major_opcode = 0;
+        let mut first_event = 0;
+        let mut first_error = 0;
+        let mut major_version = XKB_MAJOR_VERSION;
+        let mut minor_version = XKB_MINOR_VERSION;
+
+        let supported = unsafe {
+            xlib_function!(
+                display.xlib_handle(),
+                XkbQueryExtension(
+                    Some(display.raw_display()),
+                    &mut major_opcode,
+                    &mut first_event,
+                    &mut first_error,
+                    &mut major_version,
+                    &mut minor_version
+                )
+            )
+        };
+
+        if supported == xlib::False {
+            None
+        } else {
+            Some(Self {
+                display: display.clone(),
+                major_version,
+                minor_version,
+                major_opcode,
+                first_event,
+                first_error,
+            })
+        }
+    }
+
+    /// XKB version of X11 server.
+    pub fn version(&self) -> (c_int, c_int) {
+        (self.major_version, self.minor_version)
+    }
+
+    pub fn major_opcode(&self) -> c_int {
+        self.major_opcode
+    }
+
+    /// Event type of every XKB event.
+    pub fn event_type(&self) -> c_int {
+        self.first_event
+    }
+
+    pub fn first_error(&self) -> c_int {
+        self.first_error
+    }
+
+    /// Current state of the core keyboard.
+    ///
+    /// Returns error if the request failed.
+    ///
+    /// XkbGetState
+    pub fn state(&self) -> Result<XkbState, ()> {
+        let mut state: xlib::XkbStateRec = unsafe { mem::zeroed() };
+
+        let status = unsafe {
+            xlib_function!(
+                self.display.xlib_handle(),
+                XkbGetState(Some(self.display.raw_display()), XKB_USE_CORE_KBD, &mut state)
+            )
+        };
+
+        if status == xlib::Success as c_int {
+            Ok(XkbState::from_state_rec(&state))
+        } else {
+            Err(())
+        }
+    }
+
+    /// Effective group of the core keyboard.
+    ///
+    /// XkbGetState
+    pub fn current_group(&self) -> Result<c_int, ()> {
+        self.state().map(|state| state.group)
+    }
+
+    /// Names of the core keyboard's groups. Index of the name is
+    /// the group's index. Usually names are layout names like "English (US)".
+    ///
+    /// Returns error if the request failed.
+    ///
+    /// XkbAllocKeyboard, XkbGetNames, XkbFreeKeyboard, XGetAtomName, XFree
+    pub fn group_names(&self) -> Result<Vec<String>, ()> {
+        let keyboard = unsafe { xlib_function!(self.display.xlib_handle(), XkbAllocKeyboard(None)) };
+
+        if keyboard.is_null() {
+            return Err(());
+        }
+
+        let status = unsafe {
+            (*keyboard).device_spec = XKB_USE_CORE_KBD as _;
+
+            xlib_function!(
+                self.display.xlib_handle(),
+                XkbGetNames(Some(self.display.raw_display()), XKB_GROUP_NAMES_MASK, keyboard)
+            )
+        };
+
+        let group_atoms = unsafe {
+            if status != xlib::Success as c_int || (*keyboard).names.is_null() {
+                None
+            } else {
+                Some((*(*keyboard).names).groups)
+            }
+        };
+
+        unsafe {
+            xlib_function!(
+                self.display.xlib_handle(),
+                XkbFreeKeyboard(None, keyboard, 0, xlib::True)
+            );
+        }
+
+        // Groups are numbered from zero without gaps, so
+        // the first group without a name ends the list.
+        group_atoms
+            .ok_or(())?
+            .iter()
+            .take_while(|atom_id| **atom_id != 0)
+            .map(|atom_id| Atom::from_raw(*atom_id).get_name(&self.display))
+            .collect()
+    }
+
+    /// Name of the effective group.
+    ///
+    /// Returns `Ok(None)` if the group does not have a name.
+    ///
+    /// XkbGetState, XkbAllocKeyboard, XkbGetNames, XkbFreeKeyboard, XGetAtomName, XFree
+    pub fn current_layout_name(&self) -> Result<Option<String>, ()> {
+        let group = self.current_group()?;
+        let mut names = self.group_names()?;
+
+        if group >= 0 && (group as usize) < names.len() {
+            Ok(Some(names.swap_remove(group as usize)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Select XKB events of the core keyboard. Replaces previous selection.
+    ///
+    /// Events are reported as `Event::UnknownEvent` with event type
+    /// `event_type`. Convert them with `Xkb::parse_event` or
+    /// `Xkb::parse_owned_event`.
+    ///
+    /// Returns error if Xlib does not support XKB.
+    ///
+    /// XkbSelectEvents
+    pub fn select_events(&self, events: XkbEventMask) -> Result<(), ()> {
+        let result = unsafe {
+            xlib_function!(
+                self.display.xlib_handle(),
+                XkbSelectEvents(
+                    Some(self.display.raw_display()),
+                    XKB_USE_CORE_KBD,
+                    XkbEventMask::all().bits(),
+                    events.bits()
+                )
+            )
+        };
+
+        if result == xlib::False {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `None` if `event` is not an XKB event.
+    pub fn parse_event(&self, event: &xlib::XEvent) -> Option<XkbEvent> {
+        if event.get_type() != self.first_event {
+            return None;
+        }
+
+        // XKB event structures fit in `xlib::XEvent`.
+        let event_ptr = event as *const xlib::XEvent;
+
+        let event = unsafe {
+            let any_event = &*(event_ptr as *const xlib::XkbAnyEvent);
+
+            match any_event.xkb_type {
+                xlib::XkbStateNotify => {
+                    let event = &*(event_ptr as *const xlib::XkbStateNotifyEvent);
+
+                    XkbEvent::StateNotify {
+                        device: event.device,
+                        state: XkbState::from_event(event),
+                        changed: event.changed,
+                    }
+                }
+                xlib::XkbNewKeyboardNotify => {
+                    let event = &*(event_ptr as *const xlib::XkbNewKeyboardNotifyEvent);
+
+                    XkbEvent::NewKeyboardNotify {
+                        device: event.device,
+                        old_device: event.old_device,
+                        min_keycode: event.min_key_code,
+                        max_keycode: event.max_key_code,
+                    }
+                }
+                xkb_type => XkbEvent::Other(xkb_type),
+            }
+        };
+
+        Some(event)
+    }
+
+    /// Like `parse_event`, but for events from `X11Display::drain_events`
+    /// and `EventDispatcher`.
+    ///
+    /// Returns `None` if `event` is not an XKB event or it does not have
+    /// the event data. See `UnknownEvent::raw_event`.
+    pub fn parse_owned_event(&self, event: &OwnedEvent) -> Option<XkbEvent> {
+        match *event {
+            OwnedEvent::UnknownEvent(ref event) => self.parse_event(event.raw_event()?),
+            _ => None,
+        }
+    }
+
+    /// Enable or disable detectable autorepeat for this client.
+    ///
+    /// When detectable autorepeat is enabled, a held key generates
+    /// only KeyPress events and the KeyRelease event is sent when the key is
+    /// actually released. Otherwise every autorepeated KeyPress event is
+    /// preceded by a KeyRelease event.
+    ///
+    /// Returns error if X11 server does not support detectable autorepeat.
+    ///
+    /// XkbSetDetectableAutoRepeat
+    pub fn set_detectable_auto_repeat(&self, enabled: bool) -> Result<(), ()> {
+        let mut supported = xlib::False;
+
+        unsafe {
+            xlib_function!(
+                self.display.xlib_handle(),
+                XkbSetDetectableAutoRepeat(
+                    Some(self.display.raw_display()),
+                    to_xlib_bool(enabled),
+                    &mut supported
+                )
+            );
+        }
+
+        if supported == xlib::False {
+            Err(())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `None` if X11 server does not support detectable autorepeat.
+    ///
+    /// XkbGetDetectableAutoRepeat
+    pub fn detectable_auto_repeat(&self) -> Option<bool> {
+        let mut supported = xlib::False;
+
+        let enabled = unsafe {
+            xlib_function!(
+                self.display.xlib_handle(),
+                XkbGetDetectableAutoRepeat(Some(self.display.raw_display()), &mut supported)
+            )
+        };
+
+        if supported == xlib::False {
+            None
+        } else {
+            Some(enabled != xlib::False)
+        }
+    }
+}
+
+impl X11Display {
+    /// See `Xkb::new`.
+    pub fn xkb(&self) -> Option<Xkb> {
+        Xkb::new(self)
+    }
+}
@@ -7,20 +7,48 @@
 //! and your program just terminates without
 //! running any destructors.
 //!
+//! With libX11 1.7.0 or later this library prevents the termination with
+//! XSetIOErrorExitHandler. When the connection is lost, the `X11Display` is
+//! marked as dead and destructors will run normally. Use `X11Display::connection_lost`
+//! to check the connection state and `core::error::io_error_exit_handler_available`
+//! to check if your libX11 version supports this.
+//!
+//! After the connection is lost, these calls return an error or `None`:
+//! event reading methods of `X11Display`, `X11Display::flush_output_buffer`,
+//! `X11Display::sync`, `X11Display::check_connection`, `EventLoopWaker::wake`
+//! and methods with `_checked` suffix. Other calls, for example window
+//! requests, are discarded by Xlib without reporting an error, so check the
+//! connection with `X11Display::sync` after sending requests.
+//!
 //! Xlib function calls may generate errors that may occur some time after that specific
 //! function call. This happens because Xlib does buffering for X11 requests. With function `check_errors`
-//! you can check if an Xlib error is occurred. However this wrapper library prevents some
+//! you can check if an Xlib error is occurred. Errors are stored in a bounded queue for
+//! every `X11Display`. However this wrapper library prevents some
 //! errors related to wrong resource IDs, as resource ID is stored as private object attribute, but
 //! if some other X11 client will destroy that
 //! resource you will still get an error.
 //!
 //! TODO: Check which functions may make errors and document those errors.
 //!
-//! With running program with environment variable `_Xdebug` or calling a specific function, you can set
+//! With running program with environment variable `_Xdebug` or calling `X11Display::set_synchronous`, you can set
 //! Xlib to operate synchronously, so every Xlib function will wait an response from X11 server. This allows
 //! to check errors with function `check_errors` after every function call which may make an error. However this
 //! is not possible with multiple connections to X11 server in separate threads.
 //!
+//! # Multithreading
+//! With crate feature `multithreading` every Xlib function call locks the
+//! display with XLockDisplay. Use `X11Display::lock` to make a sequence of
+//! function calls atomic. The per-call locking is skipped while the
+//! current thread holds the lock. Blocking event reading methods don't
+//! lock the display while they wait for an event.
+//!
+//! Use `X11Display::create_waker` to wake a thread which is waiting for
+//! events.
+//!
+//! # Async
+//! Crate feature `async` enables `core::stream::EventStream` which
+//! implements `futures_core::Stream` with `OwnedEvent` items.
+//!
 //! # Window creation
 //! TODO: simple example
 //!
@@ -29,6 +57,13 @@
 //! at runtime. Loaded libraries however won't close properly and leave a
 //! memory leak which is [x11_dl issue](https://github.com/Daggerbot/x11-rs/issues/67), but
 //! that will not be a major problem as this wrapper library allows to load the libraries only once.
+//!
+//! # Mixing Xlib and XCB
+//! Crate feature `xlib-xcb` enables `X11Display::xcb_connection` and
+//! `X11Display::set_event_queue_owner` from library Xlib-xcb. With these functions you can
+//! use the same X11 connection with XCB bindings, for example handle
+//! events with XCB and create OpenGL contexts with this library.
+//! Feature `runtime-linking` also loads Xlib-xcb at runtime.
 
 #[cfg(not(feature = "runtime-linking"))]
 pub extern crate x11;
@@ -48,8 +83,17 @@ macro_rules! xlib_function {
             let _: *mut x11::xlib::Display = $raw_display;
             let _: &::core::XlibHandle = $xlib_handle;
 
+            // Display lock is not needed if the current thread
+            // holds a `DisplayLockGuard`.
+            #[cfg(feature = "multithreading")]
+            let lock_display = !::core::display::locked_by_current_thread($raw_display);
+
             #[cfg(feature = "multithreading")]
-            xlib_function!($xlib_handle, XLockDisplay(None, $raw_display));
+            {
+                if lock_display {
+                    xlib_function!($xlib_handle, XLockDisplay(None, $raw_display));
+                }
+            }
 
             #[cfg(not(feature = "runtime-linking"))]
             let result = {
@@ -62,7 +106,11 @@ macro_rules! xlib_function {
             };
 
             #[cfg(feature = "multithreading")]
-            xlib_function!($xlib_handle, XUnlockDisplay(None, $raw_display));
+            {
+                if lock_display {
+                    xlib_function!($xlib_handle, XUnlockDisplay(None, $raw_display));
+                }
+            }
 
             result
         }
@@ -84,18 +132,105 @@ macro_rules! xlib_function {
     };
 }
 
+/// Like `xlib_function!`, but for functions from Xlib-xcb library.
+#[cfg(feature = "xlib-xcb")]
+macro_rules! xlib_xcb_function {
+    ( $xlib_handle:expr, $function:tt ( $( $function_argument:expr ),* ) ) => {
+        {
+            let _: &::core::XlibHandle = $xlib_handle;
+
+            #[cfg(not(feature = "runtime-linking"))]
+            {
+                (::x11::xlib_xcb::$function)( $( $function_argument ,)* )
+            }
+
+            #[cfg(feature = "runtime-linking")]
+            {
+                ($xlib_handle.xlib_xcb_functions.$function)( $( $function_argument ,)* )
+            }
+        }
+    };
+}
+
+/// Declare a struct of atoms which are queried together with one round trip to
+/// the X server.
+///
+/// The macro generates method `new(&X11Display) -> Result<Self, ()>`.
+/// It returns error if some atom name is invalid or querying an atom failed.
+///
+/// ```no_run
+/// #[macro_use]
+/// extern crate x11_wrapper;
+///
+/// atoms! {
+///     pub struct EwmhAtoms {
+///         net_wm_name = "_NET_WM_NAME",
+///         utf8_string = "UTF8_STRING",
+///     }
+/// }
+///
+/// fn main() {
+///     let xlib_handle = x11_wrapper::XlibHandle::initialize_xlib().unwrap();
+///     let display = xlib_handle.create_display().unwrap();
+///
+///     let atoms = EwmhAtoms::new(&display).unwrap();
+///     println!("{:?}", atoms.net_wm_name);
+/// }
+/// ```
+#[macro_export]
+macro_rules! atoms {
+    (
+        $( #[$struct_attribute:meta] )*
+        $visibility:vis struct $struct_name:ident {
+            $( $field:ident = $atom_name:expr ),* $(,)*
+        }
+    ) => {
+        $( #[$struct_attribute] )*
+        #[derive(Debug, Clone, Copy)]
+        $visibility struct $struct_name {
+            $( pub $field: $crate::core::utils::Atom, )*
+        }
+
+        impl $struct_name {
+            /// Query all atoms with one XInternAtoms call.
+            #[allow(dead_code)]
+            pub fn new(display: &$crate::core::display::X11Display) -> Result<Self, ()> {
+                let atom_names = [
+                    $( $crate::core::utils::AtomName::new($atom_name.to_string()).map_err(|_| ())?, )*
+                ];
+
+                let mut atoms = display.intern_atoms(&atom_names, false).into_iter();
+
+                Ok(Self {
+                    $( $field: atoms.next().and_then(|atom| atom).ok_or(())?, )*
+                })
+            }
+        }
+    };
+}
+
 #[macro_use]
 extern crate bitflags;
 
 #[macro_use]
 extern crate lazy_static;
 
+extern crate libc;
+
+#[cfg(feature = "async")]
+extern crate futures_core;
+
 #[cfg_attr(not(feature = "runtime-linking"), link(name = "X11"))]
 extern "C" {}
 
+#[cfg(feature = "xlib-xcb")]
+#[cfg_attr(not(feature = "runtime-linking"), link(name = "X11-xcb"))]
+extern "C" {}
+
 pub mod core;
+pub mod extension;
 pub mod protocol;
 pub mod property;
 
 pub use core::XlibHandle;
-pub use core::error::check_error;
+pub use core::error::{check_error, check_errors};
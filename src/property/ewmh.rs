@@ -4,12 +4,19 @@
 
 use std::os::raw::c_long;
 
-use core::utils::{Atom, AtomName};
+use core::utils::Atom;
 use core::event::ClientMessageEventCreator;
 use core::display::X11Display;
 use core::window::input_output::TopLevelInputOutputWindow;
 use core::window::Window;
 
+atoms! {
+    struct NetWmStateAtoms {
+        fullscreen = "_NET_WM_STATE_FULLSCREEN",
+        net_wm_state = "_NET_WM_STATE",
+    }
+}
+
 /// Handler for `_NET_WM_STATE`.
 pub struct NetWmStateHandler {
     event: ClientMessageEventCreator,
@@ -20,22 +27,14 @@ pub struct NetWmStateHandler {
 impl NetWmStateHandler {
     /// Returns error if querying atom_name fails.
     ///
-    /// XInternAtom
+    /// XInternAtoms
     pub fn new(display: &X11Display) -> Result<Self, ()> {
-        let fullscreen_name = AtomName::new("_NET_WM_STATE_FULLSCREEN".to_string())
-            .map_err(|_| ())
-            .unwrap();
-        let fullscreen = Atom::new(display, fullscreen_name, false)?;
-
-        let net_wm_state_name = AtomName::new("_NET_WM_STATE".to_string())
-            .map_err(|_| ())
-            .unwrap();
-        let net_wm_state = Atom::new(display, net_wm_state_name, false)?;
+        let atoms = NetWmStateAtoms::new(display)?;
 
         Ok(Self {
-            fullscreen,
+            fullscreen: atoms.fullscreen,
             event: ClientMessageEventCreator::new(),
-            net_wm_state,
+            net_wm_state: atoms.net_wm_state,
         })
     }
 
@@ -105,8 +105,8 @@ impl TopLevelInputOutputWindow {
         let c_class_name = CString::new(class_name).unwrap();
 
         let mut property_data = PropertyData::<u8>::new(
-            Atom::from_raw(xlib::XA_WM_CLASS),
-            Atom::from_raw(xlib::XA_STRING),
+            Atom::WM_CLASS,
+            Atom::STRING,
         );
 
         property_data
@@ -135,8 +135,8 @@ impl TopLevelInputOutputWindow {
         height_inc: i32,
     ) -> Self {
         let mut property_data = PropertyData::<u32>::new(
-            Atom::from_raw(xlib::XA_WM_ICON_SIZE),
-            Atom::from_raw(xlib::XA_WM_ICON_SIZE),
+            Atom::WM_ICON_SIZE,
+            Atom::WM_ICON_SIZE,
         );
         {
             let data = property_data.data_mut();
@@ -184,10 +184,10 @@ pub enum TextProperty {
 impl From<TextProperty> for Atom {
     fn from(property: TextProperty) -> Self {
         match property {
-            TextProperty::ClientMachine => Atom::from_raw(xlib::XA_WM_CLIENT_MACHINE),
-            TextProperty::IconName => Atom::from_raw(xlib::XA_WM_ICON_NAME),
-            TextProperty::Command => Atom::from_raw(xlib::XA_WM_COMMAND),
-            TextProperty::Name => Atom::from_raw(xlib::XA_WM_NAME),
+            TextProperty::ClientMachine => Atom::WM_CLIENT_MACHINE,
+            TextProperty::IconName => Atom::WM_ICON_NAME,
+            TextProperty::Command => Atom::WM_COMMAND,
+            TextProperty::Name => Atom::WM_NAME,
         }
     }
 }